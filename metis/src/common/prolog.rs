@@ -9,6 +9,7 @@ use sophia_term::{
     ns::Namespace,
     TermData, TermError,
 };
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::marker::PhantomData;
 
@@ -30,7 +31,7 @@ where
 {
     _f: PhantomData<F>,
     pub(crate) base: Option<(Iri<MownStr<'td>>, IriParsed<'static>)>,
-    pub(crate) prefixes: HashMap<&'td str, Namespace<MownStr<'td>>>,
+    pub(crate) prefixes: HashMap<Cow<'td, str>, Namespace<MownStr<'td>>>,
 }
 
 impl<'td, F> Default for Prolog<'td, F>
@@ -75,7 +76,7 @@ where
             ));
         }
 
-        let base = if let Some((_, o_base)) = self.base {
+        let base = if let Some((_, o_base)) = &self.base {
             o_base.resolve(&base)
         } else {
             base
@@ -112,7 +113,7 @@ where
         if F::is_valid_prefix(&p) {
             let ns = Namespace::new(ns)?;
             let ns = self.resolve(&ns);
-            self.prefixes.insert(p, ns);
+            self.prefixes.insert(p.into(), ns);
             Ok(self)
         } else {
             Err(Error::InvalidPrefix(p.to_string()))
@@ -139,15 +140,15 @@ where
     /// Adds prefixes for `rdf`, `rdfs` and `xsd` namespaces.
     pub fn add_default_prefixes(&mut self) -> &mut Self {
         self.prefixes.insert(
-            "rdf",
+            "rdf".into(),
             Namespace::new(sophia::ns::rdf::PREFIX.into()).unwrap(),
         );
         self.prefixes.insert(
-            "rdfs",
+            "rdfs".into(),
             Namespace::new(sophia::ns::rdfs::PREFIX.into()).unwrap(),
         );
         self.prefixes.insert(
-            "xsd",
+            "xsd".into(),
             Namespace::new(sophia::ns::xsd::PREFIX.into()).unwrap(),
         );
         self
@@ -175,7 +176,7 @@ where
             let ns = ns.clone().into();
             target
                 .match_ns(&ns)
-                .map(|iter| PrologMatch::Prefix(p, iter))
+                .map(|iter| PrologMatch::Prefix(p.as_ref(), iter))
         }) {
             matched
         } else {
@@ -183,6 +184,26 @@ where
         }
     }
 
+    /// Copies the base and all prefixes so the prolog no longer borrows from
+    /// the parsed document.
+    pub fn into_owned(self) -> Prolog<'static, F> {
+        let owned = |iri: Iri<MownStr<'td>>| MownStr::from(iri.value().to_string());
+        let mut prolog = Prolog::default();
+        if let Some((base, _)) = self.base {
+            prolog
+                .set_base(Iri::new(owned(base)).expect("Base was already valid"))
+                .expect("Base was already valid");
+        }
+        prolog.prefixes = self
+            .prefixes
+            .into_iter()
+            .map(|(p, ns)| {
+                let ns = Namespace::new(owned(ns.into())).expect("Namespace was already valid");
+                (Cow::Owned(p.into_owned()), ns)
+            })
+            .collect();
+        prolog
+    }
     /// Resolves against the base IRI or returns unchanged if no base IRI is
     /// set.
     pub fn resolve<'i, I, O>(&self, other: &'i I) -> O
@@ -1,6 +1,6 @@
 //! Error handling.
 
-use crate::parse::PosError as ParserError;
+use crate::parse::{Diagnostic, Error as ParserErrorKind, Position};
 use sophia::term::TermError;
 use std::io;
 
@@ -21,11 +21,11 @@ pub enum Error {
     #[error("The text {0} is not a valid Prefix")]
     InvalidPrefix(String),
     /// The requested indentation is to wide.
-    // #[error(
-    //     "Requested to much spaces ({0}) to indent (max is {})",
-    //     crate::serialize::config::MAX_SPACES
-    // )]
-    // ToMuchSpaces(u8),
+    #[error(
+        "Requested to much spaces ({0}) to indent (max is {})",
+        crate::serialize::config::MAX_SPACES
+    )]
+    ToMuchSpaces(u8),
     /// The defined spacing is to small.
     #[error("Spacing must be at least one space")]
     InvalidSpacing,
@@ -33,16 +33,44 @@ pub enum Error {
     #[error("Target error: {0}")]
     FromIo(#[from] io::Error),
     /// Error from parsing.
-    #[error("Parser: {0}")]
-    Parser(String),
+    #[error("Parser error at {line}:{column}: {kind}")]
+    Parser {
+        /// Offset in bytes from the start of the document.
+        offset: usize,
+        /// Line number, starting at 1.
+        line: usize,
+        /// Column in characters, starting at 1.
+        column: usize,
+        /// What went wrong.
+        kind: ParserErrorKind,
+    },
     /// Error from `sophia`.
     #[error("{0}")]
     Term(#[from] TermError),
 }
 
-impl<'a> From<ParserError<'a>> for Error {
-    /// Clones the parser error's context. Removes the lifetime in turn.
-    fn from(pe: ParserError<'a>) -> Self {
-        Error::Parser(pe.to_string())
+impl Error {
+    /// Prepares a rendering of a parser error with the offending line of
+    /// `doc`.
+    ///
+    /// `doc` must be the document that was parsed. Returns `None` if this is
+    /// not a parser error.
+    pub fn diagnostic<'a>(&'a self, doc: &'a str) -> Option<Diagnostic<'a>> {
+        match self {
+            Error::Parser {
+                offset,
+                line,
+                column,
+                kind,
+            } => {
+                let position = Position {
+                    offset: *offset,
+                    line: *line,
+                    column: *column,
+                };
+                Some(Diagnostic::new(doc, position, kind))
+            }
+            _ => None,
+        }
     }
 }
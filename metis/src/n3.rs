@@ -10,12 +10,6 @@
 //! The following syntax and features not implemented according to the
 //! specification:
 //!
-//! - *Declarations:* According to the spec, `@prefix` and `@base` declarations
-//!   can be placed anywhere where a statement is valid. Relative `@base` IRI
-//!   are commutative. Here only one prolog is allowed with the `@prefix`
-//!   declarations first then at most one `@base` declaration.
-//! - *Logic quantifiers:* Only the short annotation for variables (`?x`) is
-//!   supported whereas he declarations `@forAll` and `@forSome` are not.
 //! - *Rationals:* Only the numeric literals of SPARQL are supported.
 //! - *Magic predicates:* In N3 calculations like `(2 2) math:sum ?x .` can be
 //!   calculated. These are not implemented.
@@ -28,17 +22,23 @@
 //! | Production      | Rule | Comment |
 //! | --------------- | ---- | ------- |
 //! | `document`      | `statement ('.' statement)* '.' EOF` | |
-//! | `statement`     | `directive \| simple_statement` | |
-//! | `directive`     | `prefix_id \| base` | |
+//! | `statement`     | `directive \| declaration \| simple_statement` | |
+//! | `directive`     | `prefix_id \| base \| sparql_prefix \| sparql_base \| keywords` | [3] |
+//! | `declaration`   | `('@forAll' \| '@forSome') (symbol (',' symbol)*)?` | [2] |
 //! | `prefix_id`     | `'@prefix' PNAME_NS IRIREF` | |
 //! | `base`          | `'@base' IRIREF` | |
-//! | `simple_statement` | `subject property_list` | |
+//! | `sparql_prefix` | `'PREFIX' PNAME_NS IRIREF` | from Turtle |
+//! | `sparql_base`   | `'BASE' IRIREF` | from Turtle |
+//! | `keywords`      | `'@keywords' (BARENAME (',' BARENAME)*)? '.'` | [6] |
+//! | `simple_statement` | `subject property_list?` | |
 //! | `subject`       | `expression` | |
 //! | `property_list` | `predicate object (',' object)* (';' property_list)*` | |
-//! | `predicate`     | `expression \| 'a' \| '=>'` | |
+//! | `predicate`     | `expression \| 'a' \| '=' \| '=>' \| '<=' \| 'has' expression \| 'is' expression 'of' \| '<-' expression` | [5] |
 //! | `object`        | `expression` | |
 //! | `list`          | `'(' expression* ')'` | |
-//! | `expression`    | `iri \| formula \| variable \| literal \| bnode_property_list \| list` | |
+//! | `expression`    | `path_item (('!' \| '^') path_item)*` | [4] |
+//! | `path_item`     | `symbol \| blank_node \| variable \| literal \| formula \| list \| bnode_property_list` | |
+//! | `symbol`        | `iri \| BARENAME` | [6] |
 //! | `iri`           | `IRIREF \| prefixed_name` | |
 //! | `prefixed_name` | `PNAME_LN \| PNAME_NS` | [1] |
 //! | `formula`       | `'{' ( statement ('.' statement)* )? '}'` | |
@@ -52,6 +52,27 @@
 //! | `blank_node`    | `BLANK_NODE_LABEL \| ANON` | [1] |
 //!
 //! [1] The terminals of Turtle are used.
+//!
+//! [2] Declared IRIs are replaced by `N3Term::Universal` (`@forAll`) or
+//! `N3Term::Existential` (`@forSome`) up to the end of the enclosing formula.
+//!
+//! [3] Directives may be placed wherever a statement is valid, also within
+//! formulas. They are valid from their position on up to the end of the
+//! enclosing formula. Relative base IRIs are resolved against the previous
+//! base.
+//!
+//! [4] Each step of a path is a new existential `e`. `a!p` adds the triple
+//! `a p e` while `a^p` adds `e p a`. Paths are evaluated from left to right.
+//!
+//! [5] `=` is `owl:sameAs`, `=>` is `log:implies` and `<=` the inverted
+//! `log:implies`. `is p of` and `<- p` invert `p`, i.e. subject and object
+//! are swapped. The keywords `a`, `has`, `is` and `of` may be written with a
+//! leading `@`.
+//!
+//! [6] Without `@keywords` only `a`, `has`, `is` and `of` may be written
+//! without `@` and bare names are invalid. After `@keywords` only the listed
+//! keywords may be written without `@`. All other bare names are resolved
+//! against the default prefix `:`. `true` and `false` stay boolean literals.
 
 use crate::{
     common::{RdfTerm, Valid},
@@ -62,7 +83,7 @@ use sophia::term::{
 };
 
 /// Type level representation of the Notation3 format.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct N3;
 
 impl Format for N3 {
@@ -31,6 +31,12 @@ pub mod log {
     );
 }
 
+/// Terms of the OWL vocabulary used by N3's syntax.
+#[allow(missing_docs)]
+pub mod owl {
+    sophia_term::namespace!("http://www.w3.org/2002/07/owl#", sameAs);
+}
+
 /// Terms of N3's math vocabulary.
 #[allow(missing_docs)]
 pub mod math {
@@ -68,3 +74,41 @@ pub mod math {
         tanh
     );
 }
+
+/// Prefixes and namespaces of well-known vocabularies.
+///
+/// Used to name namespaces found by
+/// [`Config::discover_prefixes()`](../serialize/struct.Config.html#method.discover_prefixes).
+/// A namespace may appear with several prefixes and vice versa.
+pub const VOCABULARIES: &[(&str, &str)] = &[
+    ("as", "https://www.w3.org/ns/activitystreams#"),
+    ("bibo", "http://purl.org/ontology/bibo/"),
+    ("dc", "http://purl.org/dc/elements/1.1/"),
+    ("dcat", "http://www.w3.org/ns/dcat#"),
+    ("dcterms", "http://purl.org/dc/terms/"),
+    ("doap", "http://usefulinc.com/ns/doap#"),
+    ("foaf", "http://xmlns.com/foaf/0.1/"),
+    ("geo", "http://www.opengis.net/ont/geosparql#"),
+    ("log", "http://www.w3.org/2000/10/swap/log#"),
+    ("math", "http://www.w3.org/2000/10/swap/math#"),
+    ("org", "http://www.w3.org/ns/org#"),
+    ("owl", "http://www.w3.org/2002/07/owl#"),
+    ("prov", "http://www.w3.org/ns/prov#"),
+    ("qudt", "http://qudt.org/schema/qudt/"),
+    ("quantitykind", "http://qudt.org/vocab/quantitykind/"),
+    ("rdf", "http://www.w3.org/1999/02/22-rdf-syntax-ns#"),
+    ("rdfs", "http://www.w3.org/2000/01/rdf-schema#"),
+    ("schema", "http://schema.org/"),
+    ("schema", "https://schema.org/"),
+    ("sh", "http://www.w3.org/ns/shacl#"),
+    ("skos", "http://www.w3.org/2004/02/skos/core#"),
+    ("sosa", "http://www.w3.org/ns/sosa/"),
+    ("ssn", "http://www.w3.org/ns/ssn/"),
+    ("time", "http://www.w3.org/2006/time#"),
+    ("unit", "http://qudt.org/vocab/unit/"),
+    ("vann", "http://purl.org/vocab/vann/"),
+    ("vcard", "http://www.w3.org/2006/vcard/ns#"),
+    ("void", "http://rdfs.org/ns/void#"),
+    ("wgs", "http://www.w3.org/2003/01/geo/wgs84_pos#"),
+    ("xsd", "http://www.w3.org/2001/XMLSchema#"),
+];
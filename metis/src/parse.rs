@@ -5,14 +5,17 @@ pub use self::util::*;
 mod error;
 pub use self::error::*;
 
-// later...
-// pub mod n3;
+pub mod n3;
 pub mod turtle;
 
 use crate::common::*;
 use sophia::term::{blank_node::BlankNode, iri::Iri, mown_str::MownStr};
+use std::borrow::Cow;
 use std::collections::VecDeque;
 
+/// Start of the labels of anonymous blank nodes, followed by a number.
+const ANON_LABEL: &str = "anon";
+
 /// The current context of the parser.
 #[derive(Debug)]
 pub struct Context<'td, F>
@@ -20,7 +23,9 @@ where
     F: Format + Valid<MownStr<'td>>,
 {
     /// Prefixes and Base
-    prolog: Prolog<'td, F>,
+    ///
+    /// May be borrowed until a directive changes it.
+    prolog: Cow<'td, Prolog<'td, F>>,
     /// Number of parsed blank nodes. Used for naming anonymous nodes.
     bnode_cnt: usize,
     /// When a list is parsed its surrounding block is parsed first. The
@@ -34,7 +39,7 @@ where
 {
     fn default() -> Self {
         Self {
-            prolog: Prolog::default(),
+            prolog: Cow::Owned(Prolog::default()),
             bnode_cnt: 0,
             triple_stack: VecDeque::new(),
         }
@@ -48,18 +53,25 @@ where
     /// Similar to `Prolog`'s method
     pub fn with_default_prefixes() -> Self {
         Self {
-            prolog: Prolog::with_default_prefixes(),
+            prolog: Cow::Owned(Prolog::with_default_prefixes()),
             bnode_cnt: 0,
             triple_stack: VecDeque::new(),
         }
     }
+    /// Labels starting with `anon` get another `anon_` in front so they never
+    /// equal the label of an anonymous node.
     fn new_labeled_bnode(&mut self, label: &'td str) -> BlankNode<MownStr<'td>> {
         self.bnode_cnt += 1;
+        let label: MownStr<'td> = if label.starts_with(ANON_LABEL) {
+            format!("{}_{}", ANON_LABEL, label).into()
+        } else {
+            label.into()
+        };
         // Should be ensured by parser.
         BlankNode::<MownStr<'td>>::new_unchecked(label)
     }
     fn new_anon_bnode(&mut self) -> BlankNode<MownStr<'td>> {
-        let label = format!("anon{}", self.bnode_cnt);
+        let label = format!("{}{}", ANON_LABEL, self.bnode_cnt);
         self.bnode_cnt += 1;
         BlankNode::<MownStr<'td>>::new_unchecked(label).into()
     }
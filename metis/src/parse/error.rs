@@ -8,28 +8,206 @@ use std::fmt;
 /// Maximal length of context given for errors.
 pub const MAX_CTX_LEN: usize = 48;
 
+/// Characters that end a token when underlining an error.
+const DELIMITERS: &str = ".,;()[]{}";
+
 /// Errors raised at parsing.
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
-    #[error("The text {0} is not a valid Prefix")]
+    /// A prefix is used that was not declared (or is invalid).
+    #[error("Undefined prefix `{0}:`")]
     InvalidPrefix(String),
+    /// An escape sequence (ECHAR or UCHAR) can not be resolved.
+    #[error("Invalid escape sequence: {0}")]
+    InvalidEscape(String),
     /// Error from `sophia`.
     #[error("{0}")]
     Term(#[from] TermError),
+    /// A production was started but could not be completed.
+    ///
+    /// Contains a description of what was expected, e.g. "`.` after triples".
+    #[error("Expected {0}")]
+    Expected(&'static str),
     /// Error from a `nom` parser.
-    #[error("Parser failed: {:?}", 0)]
+    #[error("Unexpected input ({0:?})")]
     Kind(ErrorKind),
     /// Returned if no valid rule matches.
-    #[error("No parser rule matched")]
+    #[error("Unexpected input")]
     NoMatch,
 }
 
+impl Error {
+    /// A hint how the error can be fixed.
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            Error::InvalidPrefix(p) => Some(format!(
+                "declare the prefix before its first use, e.g. `@prefix {}: <http://example.org/ns#> .`",
+                p
+            )),
+            Error::InvalidEscape(_) => Some(
+                "valid escapes are `\\t`, `\\b`, `\\n`, `\\r`, `\\f`, `\\\"`, `\\'`, `\\\\`, \
+                 `\\uXXXX` and `\\UXXXXXXXX` (no surrogates)"
+                    .to_string(),
+            ),
+            Error::Expected(_) => Some(
+                "check for a missing `.`, `;` or `,` or an unclosed bracket before this position"
+                    .to_string(),
+            ),
+            Error::Term(_) | Error::Kind(_) | Error::NoMatch => None,
+        }
+    }
+}
+
 impl From<ErrorKind> for Error {
     fn from(kind: ErrorKind) -> Self {
         Self::Kind(kind)
     }
 }
 
+/// Position of an error within a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Position {
+    /// Offset in bytes from the start of the document.
+    pub offset: usize,
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// Column in characters, starting at 1.
+    pub column: usize,
+}
+
+impl Position {
+    /// Locates the remaining input `at` within the document `doc`.
+    ///
+    /// `at` must be a slice of `doc`, as returned by the parsers. Otherwise,
+    /// the end of `doc` is assumed.
+    pub fn locate(doc: &str, at: &str) -> Self {
+        let offset = (at.as_ptr() as usize)
+            .checked_sub(doc.as_ptr() as usize)
+            .filter(|offset| *offset <= doc.len() && doc.is_char_boundary(*offset))
+            .unwrap_or_else(|| doc.len());
+        let before = doc.get(..offset).unwrap_or(doc);
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+
+        Self {
+            offset,
+            line,
+            column,
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Rendering of an error with the offending line of the document.
+///
+/// The `Display` implementation prints the error, its position, the line with
+/// the offending token underlined and, if available, a hint how to fix the
+/// error:
+///
+/// ```text
+/// error: Undefined prefix `foaf:`
+///  --> 3:7
+///   |
+/// 3 | :s :p foaf:name "Bob" .
+///   |       ^^^^^^^^^
+///   = hint: declare the prefix before its first use, ...
+/// ```
+///
+/// If `position` does not point into the document the source snippet is
+/// omitted.
+#[derive(Debug, Clone)]
+pub struct Diagnostic<'a> {
+    /// The line of the document containing the error and the number of
+    /// characters to underline.
+    snippet: Option<(&'a str, usize)>,
+    /// Where the error occurred.
+    position: Position,
+    /// What went wrong.
+    kind: &'a Error,
+}
+
+impl<'a> Diagnostic<'a> {
+    /// Creates a diagnostic for an error at `position` within `doc`.
+    pub fn new(doc: &'a str, position: Position, kind: &'a Error) -> Self {
+        let offset = position.offset;
+        let snippet = doc.get(..offset).map(|before| {
+            let after = &doc[offset..];
+            let start = before.rfind('\n').map_or(0, |idx| idx + 1);
+            let end = after.find('\n').map_or(doc.len(), |idx| offset + idx);
+            let line = doc[start..end].trim_end_matches('\r');
+            let width = token(after).chars().count().max(1);
+            (line, width)
+        });
+
+        Self {
+            snippet,
+            position,
+            kind,
+        }
+    }
+    /// The position of the error.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+}
+
+impl<'a> fmt::Display for Diagnostic<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let line_no = self.position.line.to_string();
+        let gutter = " ".repeat(line_no.len());
+
+        writeln!(f, "error: {}", self.kind)?;
+        write!(f, "{}--> {}", gutter, self.position)?;
+        if let Some((line, width)) = self.snippet {
+            // keep tabs so the marker is aligned with the token
+            let padding: String = line
+                .chars()
+                .take(self.position.column.saturating_sub(1))
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+
+            write!(f, "\n{} |", gutter)?;
+            write!(f, "\n{} | {}", line_no, line)?;
+            write!(f, "\n{} | {}{}", gutter, padding, "^".repeat(width))?;
+        }
+        if let Some(hint) = self.kind.hint() {
+            write!(f, "\n{} = hint: {}", gutter, hint)?;
+        }
+        Ok(())
+    }
+}
+
+/// The token at the start of `i`, i.e. everything up to the next whitespace
+/// or delimiter but at most `MAX_CTX_LEN` characters.
+///
+/// If `i` starts with a whitespace or a delimiter this single character is
+/// returned.
+fn token(i: &str) -> &str {
+    let is_end = |c: char| c.is_whitespace() || DELIMITERS.contains(c);
+    let end = match i.chars().next() {
+        None => 0,
+        Some(c) if is_end(c) => c.len_utf8(),
+        Some(_) => i
+            .char_indices()
+            .take(MAX_CTX_LEN)
+            .find(|(_, c)| is_end(*c))
+            .map_or_else(
+                || {
+                    i.char_indices()
+                        .nth(MAX_CTX_LEN)
+                        .map_or(i.len(), |(idx, _)| idx)
+                },
+                |(idx, _)| idx,
+            ),
+    };
+    &i[..end]
+}
+
 /// An error with information where it occurred.
 #[derive(Debug, thiserror::Error)]
 pub struct PosError<'a>(&'a str, Error);
@@ -44,17 +222,52 @@ impl<'a> PosError<'a> {
     pub fn failed(i: &'a str, err: impl Into<Error>) -> NErr<Self> {
         NErr::Failure(Self::new(i, err))
     }
+    /// The remaining input where the error occurred.
+    pub fn input(&self) -> &'a str {
+        self.0
+    }
+    /// What went wrong.
+    pub fn kind(&self) -> &Error {
+        &self.1
+    }
+    /// The position of the error within `doc`.
+    ///
+    /// `doc` must be the document that was parsed.
+    pub fn position(&self, doc: &str) -> Position {
+        Position::locate(doc, self.0)
+    }
+    /// Prepares a rendering of the error with the offending line of `doc`.
+    ///
+    /// `doc` must be the document that was parsed.
+    pub fn diagnostic<'d>(&'d self, doc: &'d str) -> Diagnostic<'d> {
+        Diagnostic::new(doc, self.position(doc), &self.1)
+    }
+    /// Converts into the crate's error which is independent of the
+    /// document's lifetime.
+    ///
+    /// `doc` must be the document that was parsed.
+    pub fn locate(self, doc: &str) -> crate::error::Error {
+        let Position {
+            offset,
+            line,
+            column,
+        } = self.position(doc);
+        crate::error::Error::Parser {
+            offset,
+            line,
+            column,
+            kind: self.1,
+        }
+    }
 }
 
 impl<'a> fmt::Display for PosError<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Error at: ")?;
-        if self.0.len() > MAX_CTX_LEN {
-            write!(f, "{}...", &self.0[..MAX_CTX_LEN])
+        if self.0.is_empty() {
+            write!(f, "{}, found end of document", self.1)
         } else {
-            write!(f, "{}", self.0)
-        }?;
-        write!(f, " => {}", self.1)
+            write!(f, "{}, found `{}`", self.1, token(self.0).escape_debug())
+        }
     }
 }
 
@@ -74,6 +287,17 @@ impl<'a> From<(&'a str, ErrorKind)> for PosError<'a> {
     }
 }
 
+/// Converts the error of a parser run on `doc` into the crate's error.
+///
+/// As only complete parsers are used, `nom::Err::Incomplete` is located at
+/// the end of the document.
+pub fn locate_err(doc: &str, err: NErr<PosError<'_>>) -> crate::error::Error {
+    match err {
+        NErr::Error(e) | NErr::Failure(e) => e.locate(doc),
+        NErr::Incomplete(_) => PosError::new(&doc[doc.len()..], Error::NoMatch).locate(doc),
+    }
+}
+
 /// Parser result using own error type.
 pub type PResult<'a, O> = IResult<&'a str, O, PosError<'a>>;
 
@@ -92,6 +316,35 @@ impl<'a, O> MapPR<'a, O> for IResult<&'a str, O> {
     }
 }
 
+/// Extension trait for `PResult`.
+pub trait CutPR<'a, O> {
+    /// Turns a recoverable `nom::Err::Error` into a `nom::Err::Failure`.
+    ///
+    /// Used after a production committed to a rule, e.g. after an opening
+    /// bracket, so no alternatives are tried any more.
+    fn cut(self) -> PResult<'a, O>;
+    /// Like `cut()` but a recoverable error is replaced by
+    /// `Error::Expected(expected)`.
+    ///
+    /// Failures are kept as they are more specific.
+    fn cut_expecting(self, expected: &'static str) -> PResult<'a, O>;
+}
+
+impl<'a, O> CutPR<'a, O> for PResult<'a, O> {
+    fn cut(self) -> PResult<'a, O> {
+        self.map_err(|nerr| match nerr {
+            NErr::Error(e) => NErr::Failure(e),
+            nerr => nerr,
+        })
+    }
+    fn cut_expecting(self, expected: &'static str) -> PResult<'a, O> {
+        self.map_err(|nerr| match nerr {
+            NErr::Error(PosError(i, _)) => PosError::failed(i, Error::Expected(expected)),
+            nerr => nerr,
+        })
+    }
+}
+
 /// Extension trait for `std::result::Result`.
 pub trait IntoPR<'a, O> {
     /// Build a `PResult` with a given context.
@@ -105,7 +358,8 @@ where
     /// This implementation returns in the error-case a `nom::Err::Failed(_)`
     /// as validation usually means an invalid document.
     fn into_pr(self, before: &'a str, after: &'a str) -> PResult<'a, T> {
-        self.map(|t| (after, t)).map_err(|err| PosError::failed(before, err))
+        self.map(|t| (after, t))
+            .map_err(|err| PosError::failed(before, err))
     }
 }
 
@@ -119,6 +373,73 @@ impl<'a, T> OrIntoPR<'a, T> for Option<T> {
     /// This implementation returns in the error-case a `nom::Err::Failed(_)`
     /// as validation usually means an invalid document.
     fn or_into_pr(self, before: &'a str, err: Error, after: &'a str) -> PResult<'a, T> {
-        self.map(|t| (after, t)).ok_or_else(|| PosError::failed(before, err))
+        self.map(|t| (after, t))
+            .ok_or_else(|| PosError::failed(before, err))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("foaf:name rest" => "foaf:name" ; "name")]
+    #[test_case(".rest" => "." ; "delimiter")]
+    #[test_case("<http://ex/>." => "<http://ex/>" ; "until delimiter")]
+    #[test_case(" rest" => " " ; "whitespace")]
+    #[test_case("" => "" ; "empty")]
+    fn check_token(i: &str) -> &str {
+        token(i)
+    }
+
+    #[test]
+    fn check_diagnostic() {
+        let doc = "@prefix : <http://ex/> .\n:s :p foaf:name .\n";
+        let err = PosError::new(
+            &doc[doc.find("foaf").unwrap()..],
+            Error::InvalidPrefix("foaf".into()),
+        );
+        assert_eq!(
+            err.diagnostic(doc).to_string(),
+            "error: Undefined prefix `foaf:`\n \
+             --> 2:7\n  \
+             |\n\
+             2 | :s :p foaf:name .\n  \
+             |       ^^^^^^^^^\n  \
+             = hint: declare the prefix before its first use, e.g. `@prefix foaf: <http://example.org/ns#> .`"
+        );
+
+        let doc = "\t<s> <p> <o>";
+        let err = PosError::new(&doc[doc.len()..], Error::Expected("`.` after triples"));
+        let rendered = err.diagnostic(doc).to_string();
+        assert!(rendered.starts_with("error: Expected `.` after triples\n --> 1:13\n"));
+        assert!(rendered.contains("1 | \t<s> <p> <o>\n  | \t           ^\n"));
+        assert_eq!(
+            err.to_string(),
+            "Expected `.` after triples, found end of document"
+        );
+    }
+
+    #[test_case(3, 1, 0 => "error: Unexpected input\n --> 1:0\n  |\n1 | äbc\n  | ^" ; "column zero")]
+    #[test_case(42, 3, 1 => "error: Unexpected input\n --> 3:1" ; "beyond end")]
+    #[test_case(1, 1, 2 => "error: Unexpected input\n --> 1:2" ; "no char boundary")]
+    fn check_diagnostic_invalid_position(offset: usize, line: usize, column: usize) -> String {
+        let kind = Error::NoMatch;
+        let position = Position {
+            offset,
+            line,
+            column,
+        };
+        Diagnostic::new("äbc", position, &kind).to_string()
+    }
+
+    #[test_case("abc", 0 => (0, 1, 1) ; "start")]
+    #[test_case("abc", 2 => (2, 1, 3) ; "first line")]
+    #[test_case("ab\ncd\nef", 7 => (7, 3, 2) ; "third line")]
+    #[test_case("äb\nöd", 6 => (6, 2, 2) ; "multibyte")]
+    #[test_case("ab\n", 3 => (3, 2, 1) ; "end")]
+    fn check_locate(doc: &str, offset: usize) -> (usize, usize, usize) {
+        let pos = Position::locate(doc, &doc[offset..]);
+        (pos.offset, pos.line, pos.column)
     }
 }
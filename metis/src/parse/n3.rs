@@ -13,100 +13,141 @@
 pub mod production;
 pub mod terminals;
 
-use super::turtle::terminals as ttl_terminal;
+use self::terminals::VARIABLE;
+use super::turtle::terminals::multispace0;
 use crate::error::Result;
-use crate::parse::Context;
-use crate::CowTerm as FormatCowTerm;
+use crate::n3::Formula;
+use crate::parse::{locate_err, Context};
 use crate::N3;
-use std::borrow::Cow;
-use std::cell::RefCell;
+use sophia::term::{iri::Iri, mown_str::MownStr};
+use std::collections::{HashMap, HashSet};
 
-/// Shortcut for `Term<Cow<'a, str>>`.
-pub type CowTerm<'a> = FormatCowTerm<'a, N3>;
+/// Shortcut for `N3Term<MownStr<'doc>>`.
+pub type MownTerm<'doc> = crate::MownTerm<'doc, N3>;
 
-/// The Turtle parser that parses a document step by step.
-pub struct Parser<'a> {
+/// Shortcut for `Formula<MownStr<'doc>>`.
+pub type MownFormula<'doc> = Formula<MownStr<'doc>>;
+
+/// The N3 parser that parses a document step by step.
+pub struct Parser<'doc> {
+    /// The whole document. Used to locate errors.
+    doc: &'doc str,
     /// Gathered metadata.
-    ctx: RefCell<Context<'a, N3>>,
+    ctx: Context<'doc, N3>,
     /// Current position within the document.
-    current: &'a str,
-    /// true if the parser failed once of is at EOF.
+    current: &'doc str,
+    /// true if the parser failed once or if it is at EOF.
     ///
     /// In both cases the `next() = None`.
     end_or_failed: bool,
+    /// The IRIs declared by `@forAll` and `@forSome` mapped to their
+    /// quantified terms.
+    ///
+    /// There is one scope per formula, the first one is the document itself.
+    quantifiers: Vec<HashMap<String, MownTerm<'doc>>>,
+    /// Names that universals declared by `@forAll` must not use, i.e. the
+    /// names of the declared universals and of all `?` variables.
+    universals: HashSet<String>,
+    /// The keywords declared by `@keywords` that may be used without `@`.
+    ///
+    /// `None` if the document has no `@keywords` directive.
+    bare_keywords: Option<HashSet<&'doc str>>,
 }
 
-impl<'a> Parser<'a> {
+impl<'doc> Parser<'doc> {
     /// Creates a new Parser.
-    pub fn new(doc: &'a str) -> Self {
+    pub fn new(doc: &'doc str) -> Self {
         // trim leading whitespaces
-        let (doc, _) = ttl_terminal::multispace0(doc).unwrap();
+        let (current, _) = multispace0(doc).unwrap();
         Self {
-            ctx: RefCell::new(Context::default()),
-            current: doc,
+            doc,
+            ctx: Context::default(),
+            current,
             end_or_failed: false,
+            quantifiers: vec![HashMap::new()],
+            universals: variable_names(doc),
+            bare_keywords: None,
         }
     }
     /// A new parser with a pre-set base IRI to resolve `iri` productions.
     ///
     /// _Note:_ If the document contains an own `base` directive the pre-set
     /// value is overridden.
-    pub fn with_base(doc: &'a str, base: impl Into<Cow<'a, str>>) -> Result<Self> {
+    pub fn with_base(doc: &'doc str, base: Iri<MownStr<'doc>>) -> Result<Self> {
         let mut ctx = Context::default();
-        ctx.prolog.set_base(base.into())?;
+        ctx.prolog.to_mut().set_base(base)?;
 
         // trim leading whitespaces
-        let (doc, _) = ttl_terminal::multispace0(doc).unwrap();
+        let (current, _) = multispace0(doc).unwrap();
         Ok(Self {
-            ctx: RefCell::new(ctx),
-            current: doc,
+            doc,
+            ctx,
+            current,
             end_or_failed: false,
+            quantifiers: vec![HashMap::new()],
+            universals: variable_names(doc),
+            bare_keywords: None,
         })
     }
 }
 
-impl<'a> Iterator for Parser<'a> {
-    type Item = Result<[CowTerm<'a>; 3]>;
+/// Collects the names of all `?` variables of the document.
+///
+/// Also finds `?` in strings and comments. This only changes the names that
+/// are generated for universals.
+fn variable_names(doc: &str) -> HashSet<String> {
+    doc.match_indices('?')
+        .filter_map(|(idx, _)| VARIABLE.find(&doc[idx..]))
+        .map(|m| m.as_str()[1..].to_string())
+        .collect()
+}
+
+impl<'doc> Iterator for Parser<'doc> {
+    type Item = Result<[MownTerm<'doc>; 3]>;
 
     /// Returns parsed triples.
     ///
     /// The parsing is done statement per statement. The parsed triples from
     /// a statement are stored internally. When all triples of a parsed
     /// statement are returned the next statement is parsed.
+    ///
+    /// Triples within formulas are not returned on their own but as part of
+    /// the `N3Term::Formula` they belong to.
     fn next(&mut self) -> Option<Self::Item> {
-        None
-        // if self.end_or_failed {
-        //     // parser finished
-        //     return None;
-        // } else if let Some(tri) = self.ctx.borrow_mut().pop_triple() {
-        //     // triples are left from the last parsing
-        //     return Some(Ok(tri));
-        // } else if self.current.is_empty() {
-        //     // parser has finished but has it not yet recognized
-        //     self.end_or_failed = true;
-        //     return None;
-        // }
-
-        // // parse new triples
-        // let step = statement(&self.current, &self.ctx);
-        // let rest = match step {
-        //     Ok((rest, _)) => rest,
-        //     Err(e) => {
-        //         self.end_or_failed = true;
-        //         return Some(Err(Error::Parser(e.to_string())));
-        //     }
-        // };
-        // // multispace0 never fails
-        // let (rest, _) = multispace0(rest).unwrap();
-        // self.current = rest;
-
-        // self.next()
+        if self.end_or_failed {
+            // parser finished
+            return None;
+        } else if let Some(tri) = self.ctx.pop_triple() {
+            // triples are left from the last parsing
+            return Some(Ok(tri));
+        } else if self.current.is_empty() {
+            // parser has finished but has it not yet recognized
+            self.end_or_failed = true;
+            return None;
+        }
+
+        // parse new triples
+        let step = self.statement(self.current);
+        let rest = match step {
+            Ok((rest, _)) => rest,
+            Err(e) => {
+                self.end_or_failed = true;
+                return Some(Err(locate_err(self.doc, e)));
+            }
+        };
+        // multispace0 never fails
+        let (rest, _) = multispace0(rest).unwrap();
+        self.current = rest;
+
+        self.next()
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::n3::N3Term;
+    use crate::ns::log;
 
     #[test]
     fn parse_turtle() -> std::result::Result<(), Box<dyn std::error::Error>> {
@@ -162,9 +203,10 @@ mod test {
           rdfs:label "the height of tree #125"@en .
         "#;
 
-        let g = Parser::new(example).collect::<Result<Vec<[CowTerm<'_>; 3]>, _>>()?;
+        let g = Parser::new(example).collect::<Result<Vec<_>>>()?;
 
         println!("Serialized: \n\n {:#?}", g);
+        assert_eq!(g.len(), 29);
         Ok(())
     }
 
@@ -222,9 +264,123 @@ mod test {
         } => { ?id :sqrt ?res . } .
         "#;
 
-        let g = Parser::new(example).collect::<Result<Vec<[CowTerm<'_>; 3]>, _>>()?;
+        let g = Parser::new(example).collect::<Result<Vec<_>>>()?;
 
         println!("Serialized: \n\n {:#?}", g);
+        assert_eq!(g.len(), 5);
+        let rules: Vec<_> = g
+            .iter()
+            .filter(|[_, p, _]| *p == MownTerm::from(log::iri::implies.clone_into()))
+            .collect();
+        assert_eq!(rules.len(), 3);
+        // the bodies of the rules
+        let sizes: Vec<_> = rules
+            .iter()
+            .map(|[s, _, o]| match (s, o) {
+                (N3Term::Formula(s), N3Term::Formula(o)) => (s.len(), o.len()),
+                _ => panic!("Rules must relate formulas"),
+            })
+            .collect();
+        assert_eq!(sizes, vec![(6, 3), (26, 3), (5, 1)]);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_formula() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let example = r#"@prefix : <http://example.org/> .
+        :a :says { :b :says { :c :p ( 1 ?x ) } . :d :p :o } .
+        { } => { [ :p :o ] } .
+        "#;
+
+        let g = Parser::new(example).collect::<Result<Vec<_>>>()?;
+        assert_eq!(g.len(), 2);
+        let outer = match &g[0][2] {
+            N3Term::Formula(f) => f,
+            t => panic!("Expected a formula but got {:?}", t),
+        };
+        assert_eq!(outer.len(), 2);
+        match &outer[0][2] {
+            // the triple with the list and the list's triples
+            N3Term::Formula(inner) => assert_eq!(inner.len(), 5),
+            t => panic!("Expected a formula but got {:?}", t),
+        }
+        assert!(matches!(&g[1][0], N3Term::Formula(f) if f.is_empty()));
+        assert!(matches!(&g[1][2], N3Term::Formula(f) if f.len() == 1));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_quantifiers() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let example = r#"@prefix : <http://example.org/> .
+        @forAll :x .
+        { :x a :Man } => { :x a :Mortal } .
+        { @forSome :y . :y :loves :x } => { :x a :Loved } .
+        "#;
+
+        let g = Parser::new(example).collect::<Result<Vec<_>>>()?;
+        assert_eq!(g.len(), 2);
+        let (body, head) = match &g[1] {
+            [N3Term::Formula(body), _, N3Term::Formula(head)] => (body, head),
+            t => panic!("Expected a rule but got {:?}", t),
+        };
+        assert!(matches!(&body[0][0], N3Term::Existential(_)));
+        assert!(matches!(&body[0][2], N3Term::Universal(_)));
+        assert_eq!(body[0][2], head[0][0]);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_universal_and_variable() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let example = r#"@prefix : <http://example.org/> .
+        @forAll :x .
+        :x :p ?x .
+        "#;
+
+        let g = Parser::new(example).collect::<Result<Vec<_>>>()?;
+        assert_eq!(g.len(), 1);
+        assert!(matches!(&g[0][0], N3Term::Universal(_)));
+        assert!(matches!(&g[0][2], N3Term::Universal(_)));
+        assert_ne!(g[0][0], g[0][2]);
         Ok(())
     }
+
+    #[test]
+    fn parse_directives_scoped_to_formula() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let example = r#"@base <http://example.org/> .
+        <s> <p> { @prefix : <a#> . @keywords . :s @a :o . BASE <b/> :s :p <o> } .
+        PREFIX : <c#>
+        :s a <o> .
+        "#;
+
+        let g = Parser::new(example).collect::<Result<Vec<_>>>()?;
+        assert_eq!(g.len(), 2);
+        let f = match &g[0][2] {
+            N3Term::Formula(f) => f,
+            t => panic!("Expected a formula but got {:?}", t),
+        };
+        let iri = |t: &MownTerm| match t {
+            N3Term::Iri(iri) => iri.value().to_string(),
+            t => panic!("Expected an IRI but got {:?}", t),
+        };
+        assert_eq!(iri(&f[0][0]), "http://example.org/a#s");
+        assert_eq!(iri(&f[1][2]), "http://example.org/b/o");
+        // directives within formulas do not apply outside
+        assert_eq!(iri(&g[1][0]), "http://example.org/c#s");
+        assert_eq!(iri(&g[1][2]), "http://example.org/o");
+
+        let example = "<http://example.org/s> <http://example.org/p> { @prefix ex: <http://example.org/> . } .\n\
+                       ex:s ex:p ex:o .";
+        let mut parser = Parser::new(example);
+        assert!(matches!(parser.next(), Some(Ok(_))));
+        assert!(matches!(parser.next(), Some(Err(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_missing_dot() {
+        let example = "@prefix : <http://example.org/> .\n{ :s :p :o } => { :s :q :o }";
+        let mut parser = Parser::new(example);
+        assert!(matches!(parser.next(), Some(Err(_))));
+        assert!(parser.next().is_none());
+    }
 }
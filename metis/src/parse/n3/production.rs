@@ -1,381 +1,1074 @@
 //! Production rules of N3.
 //!
-//! # Relation Turtle parsers
+//! # Relation to the Turtle parser
 //!
-//! As Turtle is a subset of Notation3 many Turtle parsers could be used here.
-//! However, both parsers use different `Context`'s So they can not be mixed.
-//! The general strategie is to fic bugs in the Turtle parser and copy them to
-//! N3 if suitable. Not very nice but for now okay.
+//! As Turtle is a subset of Notation3 many productions of Turtle could be
+//! used here. However, both parsers use different `Context`s so they can not
+//! be mixed. The _pure_ nom-parsers of Turtle are reused, while the methods
+//! building the terms are copied. The general strategy is to fix bugs in the
+//! Turtle parser and copy them to N3 if suitable. Not very nice but for now
+//! okay.
 //!
-//! # Known issues
+//! # Formulas
 //!
-//! The internal stack of the context is suitable for Turtle but not for
-//! formats with multiple graphs, e.g. N3's formulae.
-//! => the stack must be removed and parsed triples returned alongside the
-//! parser.
+//! Parsed triples are pushed onto the stack of the parser's context. When a
+//! formula is parsed the stack is swapped with an empty one. Hence, all
+//! triples parsed within the formula are gathered on their own stack, which
+//! becomes the content of the formula afterwards.
 //!
+//! # Quantifiers
+//!
+//! IRIs declared by `@forAll` or `@forSome` are replaced by a
+//! `N3Term::Universal` or `N3Term::Existential` respectively. A declaration
+//! is valid from its position to the end of the enclosing formula, including
+//! nested formulas.
 
-use super::terminals::*;
-use super::CowTerm;
-use crate::n3::{Formula, N3Term};
-use crate::ns::log;
+use super::{terminals::*, MownFormula, MownTerm, Parser};
+use crate::ns::{log, owl};
+use crate::parse::turtle::production::{
+    anon, base, boolean_literal, iriref, lexical_value, local_escape, numeric_escape,
+    pname_ln_split, pname_ns, prefix, sparql_base, sparql_prefix, string_escape,
+};
+use crate::parse::turtle::terminals::{
+    blank_node_label, multispace0, DECIMAL, DOUBLE, INTEGER, LANGTAG,
+};
 use crate::parse::{
-    parse_regex, turtle::production as ttl_production, turtle::terminals as ttl_terminal,
-    unwrap_str, Context,
+    parse_regex, CutPR as _, Error, IntoPR as _, MapPR as _, OrIntoPR as _, PResult, PosError,
 };
-use crate::N3;
 use nom::branch::alt;
-use nom::bytes::complete::{tag, tag_no_case};
-use nom::combinator::{map, map_opt, opt};
-use nom::multi::{many0, separated_list};
-use nom::sequence::{preceded, tuple};
-use nom::{error::ErrorKind, error_position, Err as NomError, IResult};
+use nom::bytes::complete::tag;
+use nom::combinator::map;
+use nom::error::ErrorKind;
+use nom::sequence::preceded;
+use nom::{Err as NomError, IResult};
 use sophia::ns::{rdf, xsd};
-use sophia::term::Term;
-use std::borrow::Cow;
-use std::cell::RefCell;
+use sophia::term::{
+    blank_node::BlankNode, iri::Iri, literal::Literal, mown_str::MownStr, variable::Variable,
+};
+use std::collections::{HashMap, HashSet};
+use std::mem;
 
-/// A context wrapped in a RefCell.
-///
-/// This is necessary due to the constraints of `nom`'s parser generators (they
-/// only take `Fn`).
-pub type RefContext<'a> = RefCell<Context<'a, N3>>;
+/// Keywords that may be used without `@` if there is no `@keywords`
+/// directive.
+const DEFAULT_KEYWORDS: &[&str] = &["a", "has", "is", "of"];
 
-/// Formula with Cow
-pub type CowFormula<'a> = Formula<Cow<'a, str>>;
+// N3 rules.
+impl<'doc> Parser<'doc> {
+    /// Parses N3's production
+    /// `statement ::= directive | (declaration | simple_statement) '.'`
+    ///
+    /// All parsed triples are pushed onto the context's stack.
+    pub fn statement(&mut self, i: &'doc str) -> PResult<'doc, ()> {
+        match self.directive(i) {
+            Err(NomError::Error(_)) => {}
+            res => return res,
+        };
 
-/// Triple of `N3Term`s
-pub type N3Triple<'a> = [CowTerm<'a>; 3];
+        let (rest, _) = self.alt(i, &[&Self::declaration, &Self::simple_statement])?;
+        let (rest, _) = multispace0(rest).map_pr()?;
+        let (rest, _) = tag(".")(rest)
+            .map_pr()
+            .cut_expecting("`.` after the statement")?;
+        Ok((rest, ()))
+    }
 
-/// Iterator of `N3Term`s
-pub type N3TriplesIter<'a> = Box<dyn 'a + Iterator<Item = [CowTerm<'a>; 3]>>;
+    /// Parses N3's production
+    /// `directive ::= prefix_id | base | sparql_prefix | sparql_base | keywords`
+    ///
+    /// Directives are allowed between any statements, also within formulas.
+    /// They are valid from their position on.
+    pub fn directive(&mut self, i: &'doc str) -> PResult<'doc, ()> {
+        self.alt(
+            i,
+            &[
+                &Self::prefix,
+                &Self::base,
+                &Self::sparql_prefix,
+                &Self::sparql_base,
+                &Self::keywords,
+            ],
+        )
+    }
 
-/// A `Vec` of `N3Triple`s
-pub type N3TriplesList<'a> = Vec<N3Triple<'a>>;
+    /// Parses N3's production
+    /// `keywords ::= '@keywords' (BARENAME (',' BARENAME)*)? '.'`
+    ///
+    /// From now on only the listed keywords may be written without `@`. All
+    /// other bare names are resolved against the default prefix.
+    pub fn keywords(&mut self, i: &'doc str) -> PResult<'doc, ()> {
+        let (rest, _) = self.keyword(i, "keywords")?;
+        let (mut rest, _) = multispace0(rest).map_pr()?;
+        let mut keywords = HashSet::new();
 
-/// Used when an iterator is reuiqured but none provided.
-fn empty_iter<'a>() -> N3TriplesIter<'a> {
-    Box::new(None.into_iter()) as _
-}
+        // the list of keywords may be empty
+        if let Ok((r, kw)) = parse_regex(&BARENAME)(rest).map_pr() {
+            keywords.insert(kw);
+            rest = r;
+            loop {
+                let (r, _) = multispace0(rest).map_pr()?;
+                let r = match tag(",")(r).map_pr() {
+                    Ok((r, _)) => r,
+                    Err(_) => break,
+                };
+                let (r, _) = multispace0(r).map_pr()?;
+                let (r, kw) = parse_regex(&BARENAME)(r)
+                    .map_pr()
+                    .cut_expecting("a keyword after `,`")?;
+                keywords.insert(kw);
+                rest = r;
+            }
+        }
 
-/// Parses rule `statement`
-pub fn statement<'a>(i: &'a str, ctx: &RefContext<'a>) -> IResult<&'a str, N3TriplesList<'a>> {
-    unimplemented!()
-}
+        let (rest, _) = multispace0(rest).map_pr()?;
+        let (rest, _) = tag(".")(rest)
+            .map_pr()
+            .cut_expecting("`.` after the keywords")?;
+        self.bare_keywords = Some(keywords);
+        Ok((rest, ()))
+    }
 
-/// Parses rule `subject`
-pub fn subject<'a>(
-    i: &'a str,
-    ctx: &RefContext<'a>,
-) -> IResult<&'a str, (CowTerm<'a>, N3TriplesIter<'a>)> {
-    expression(i, ctx)
-}
+    /// Parses N3's production
+    /// `declaration ::= ('@forAll' | '@forSome') (symbol (',' symbol)*)?`
+    ///
+    /// The declared IRIs are added to the scope of the current formula.
+    pub fn declaration(&mut self, i: &'doc str) -> PResult<'doc, ()> {
+        let (rest, universal) = match self.keyword(i, "forAll") {
+            Ok((rest, _)) => (rest, true),
+            Err(NomError::Error(_)) => (self.keyword(i, "forSome")?.0, false),
+            Err(e) => return Err(e),
+        };
+        let (r, _) = multispace0(rest).map_pr()?;
 
-/// Parses rule `property_list`.
-///
-/// All parserd triples are pushed onto the context's stack.
-pub fn property_list<'a, 's>(
-    i: &'a str,
-    s: CowTerm<'a>,
-    ctx: &RefContext<'a>,
-) -> IResult<&'a str, N3TriplesIter<'a>> {
-    let (rest, list) = separated_list(
-        tuple((
-            ttl_terminal::multispace0,
-            tag(";"),
-            ttl_terminal::multispace0,
-        )),
-        map(
-            tuple((
-                |i| predicate(i, ctx),
-                ttl_terminal::multispace1,
-                |i| object_list(i, ctx),
-            )),
-            |((verb, v_others), _, (objects, o_others))| {
-                // TODO: dont allocate an extra vector
-                let base: Vec<_> = objects
-                    .into_iter()
-                    .map(|o| [s.clone(), verb.clone(), o])
-                    .collect();
-                base.into_iter().chain(v_others).chain(o_others)
-            },
-        ),
-    )(i)?;
-
-    if list.len() == 0 {
-        return Err(NomError::Error(error_position!(rest, ErrorKind::Verify)));
-    }
-
-    Ok((rest, Box::new(list.into_iter().flatten())))
-}
+        // the list of IRIs may be empty
+        let mut rest = match self.symbol(r) {
+            Ok((r, iri)) => {
+                self.declare(iri, universal);
+                r
+            }
+            Err(NomError::Error(_)) => return Ok((rest, ())),
+            Err(e) => return Err(e),
+        };
 
-/// Parses rule `object_list`
-pub fn object_list<'a>(
-    i: &'a str,
-    ctx: &RefContext<'a>,
-) -> IResult<&'a str, (Vec<CowTerm<'a>>, N3TriplesIter<'a>)> {
-    let (rest, list) = separated_list(
-        tuple((
-            ttl_terminal::multispace0,
-            tag(","),
-            ttl_terminal::multispace0,
-        )),
-        |i| object(i, ctx),
-    )(i)?;
-
-    if list.len() == 0 {
-        Err(NomError::Error(error_position!(rest, ErrorKind::Verify)))
-    } else {
-        let mut objects = vec![];
-        let mut others = vec![];
-        list.into_iter().for_each(|(object, iter)| {
-            objects.push(object);
-            others.push(iter);
-        });
-
-        Ok((rest, (objects, Box::new(others.into_iter().flatten()))))
+        loop {
+            let (r, _) = multispace0(rest).map_pr()?;
+            let r = match tag(",")(r).map_pr() {
+                Ok((r, _)) => r,
+                Err(_) => return Ok((rest, ())),
+            };
+            let (r, _) = multispace0(r).map_pr()?;
+            let (r, iri) = self.symbol(r).cut_expecting("an IRI after `,`")?;
+            self.declare(iri, universal);
+            rest = r;
+        }
     }
-}
 
-/// Parses rule `predicate`
-pub fn predicate<'a>(
-    i: &'a str,
-    ctx: &RefContext<'a>,
-) -> IResult<&'a str, (CowTerm<'a>, N3TriplesIter<'a>)> {
-    alt((
-        |i| expression(i, ctx),
-        map(tag("a"), |_| (Term::from(&rdf::type_).into(), empty_iter())),
-        map(tag("=>"), |_| {
-            (Term::from(&log::implies).into(), empty_iter())
-        }),
-    ))(i)
-}
+    /// Parses N3's production
+    /// `simple_statement ::= subject property_list?`
+    ///
+    /// All parsed triples are pushed onto the context's stack.
+    pub fn simple_statement(&mut self, i: &'doc str) -> PResult<'doc, ()> {
+        let (rest, s) = self.subject(i)?;
+        let (after_ws, _) = multispace0(rest).map_pr()?;
+        match self.property_list(after_ws, &s) {
+            Err(NomError::Error(_)) => Ok((rest, ())),
+            res => res,
+        }
+    }
 
-/// Parses rule `object`
-pub fn object<'a>(
-    i: &'a str,
-    ctx: &RefContext<'a>,
-) -> IResult<&'a str, (CowTerm<'a>, N3TriplesIter<'a>)> {
-    expression(i, ctx)
-}
+    /// Parses N3's production
+    /// `subject ::= expression`
+    pub fn subject(&mut self, i: &'doc str) -> PResult<'doc, MownTerm<'doc>> {
+        self.expression(i)
+    }
 
-/// Parses rule `list`
-///
-/// # Result
-///
-/// Returns the subject of the first element in the list. If the collection is
-/// empty `rdf:nil` is returned. In addition, the triples representing the rest
-/// of the list are pushed on the context's stack.
-pub fn list<'a>(
-    i: &'a str,
-    ctx: &RefContext<'a>,
-) -> IResult<&'a str, (CowTerm<'a>, N3TriplesIter<'a>)> {
-    let (rest, _) = tag("(")(i)?;
-    let (rest, _) = ttl_terminal::multispace0(rest)?;
-    let (rest, contents) = separated_list(ttl_terminal::multispace1, |i| expression(i, ctx))(rest)?;
-    let (rest, _) = ttl_terminal::multispace0(rest)?;
-    let (rest, _) = tag(")")(rest)?;
-
-    if contents.is_empty() {
-        Ok((rest, (Term::from(&rdf::nil).into(), empty_iter())))
-    } else {
-        let mut cur;
-        let mut next = ctx.borrow_mut().new_anon_bnode();
-        let first = next.clone();
-        let max = contents.len() - 1;
-        let mut triples = Vec::with_capacity(contents.len());
-        let mut others = Vec::with_capacity(contents.len());
-
-        for (idx, (o, other)) in contents.into_iter().enumerate() {
-            cur = next;
-            next = if idx != max {
-                ctx.borrow_mut().new_anon_bnode()
-            } else {
-                Term::from(&rdf::nil).into()
+    /// Parses N3's production
+    /// `property_list ::= predicate object (',' object)* (';' property_list)*`
+    ///
+    /// Like in Turtle, repeated and trailing `;` are allowed. All parsed
+    /// triples are pushed onto the context's stack.
+    pub fn property_list(&mut self, i: &'doc str, s: &MownTerm<'doc>) -> PResult<'doc, ()> {
+        let (rest, (p, inverse)) = self.predicate(i)?;
+        let (rest, _) = multispace0(rest).map_pr()?;
+        let (mut rest, _) = self
+            .object_list(rest, s, &p, inverse)
+            .cut_expecting("an object after the predicate")?;
+
+        loop {
+            let (r, _) = multispace0(rest).map_pr()?;
+            let r = match tag(";")(r).map_pr() {
+                Ok((r, _)) => r,
+                Err(_) => return Ok((rest, ())),
+            };
+            rest = r;
+
+            // `predicate object_list` is optional after `;`
+            let (r, _) = multispace0(rest).map_pr()?;
+            match self.predicate(r) {
+                Ok((r, (p, inverse))) => {
+                    let (r, _) = multispace0(r).map_pr()?;
+                    let (r, _) = self
+                        .object_list(r, s, &p, inverse)
+                        .cut_expecting("an object after the predicate")?;
+                    rest = r;
+                }
+                Err(NomError::Error(_)) => {}
+                Err(e) => return Err(e),
             };
-            triples.push([cur.clone(), Term::from(&rdf::first).into(), o]);
-            triples.push([cur, Term::from(&rdf::rest).into(), next.clone()]);
-            others.push(other);
         }
+    }
 
-        Ok((
-            rest,
-            (
-                first,
-                Box::new(triples.into_iter().chain(others.into_iter().flatten())),
-            ),
-        ))
+    /// Parses the objects of N3's production `property_list`
+    /// `object (',' object)*`
+    ///
+    /// If `inverse` is set subject and object of the triples are swapped. All
+    /// parsed triples are pushed onto the context's stack.
+    pub fn object_list(
+        &mut self,
+        i: &'doc str,
+        s: &MownTerm<'doc>,
+        p: &MownTerm<'doc>,
+        inverse: bool,
+    ) -> PResult<'doc, ()> {
+        let (mut rest, o) = self.object(i)?;
+        self.push_statement(s, p, o, inverse);
+
+        loop {
+            let (r, _) = multispace0(rest).map_pr()?;
+            let r = match tag(",")(r).map_pr() {
+                Ok((r, _)) => r,
+                Err(_) => return Ok((rest, ())),
+            };
+            let (r, _) = multispace0(r).map_pr()?;
+            let (r, o) = self.object(r).cut_expecting("an object after `,`")?;
+            self.push_statement(s, p, o, inverse);
+            rest = r;
+        }
     }
-}
 
-/// Parses rule `expression`
-///
-/// Returns the 'value' of the expression and triples parsed while evaluating.
-pub fn expression<'a>(
-    i: &'a str,
-    ctx: &RefContext<'a>,
-) -> IResult<&'a str, (CowTerm<'a>, N3TriplesIter<'a>)> {
-    alt((
-        |i| iri(i, ctx).map(|(r, t)| (r, (t, empty_iter()))),
-        |i| formula(i, ctx).map(|(r, f)| (r, (f.into(), empty_iter()))),
-        |i| variable(i).map(|(r, t)| (r, (t, empty_iter()))),
-        |i| literal(i, ctx).map(|(r, t)| (r, (t, empty_iter()))),
-        |i| list(i, ctx),
-        |i| bnode_property_list(i, ctx),
-    ))(i)
-}
+    /// Pushes the triple `s p o` onto the context's stack or `o p s` if
+    /// `inverse` is set.
+    fn push_statement(
+        &mut self,
+        s: &MownTerm<'doc>,
+        p: &MownTerm<'doc>,
+        o: MownTerm<'doc>,
+        inverse: bool,
+    ) {
+        if inverse {
+            self.ctx.push_triple([o, p.clone(), s.clone()]);
+        } else {
+            self.ctx.push_triple([s.clone(), p.clone(), o]);
+        }
+    }
+
+    /// Parses N3's production
+    /// `predicate ::= expression | 'a' | '=' | '=>' | '<=' | 'has' expression | 'is' expression 'of' | '<-' expression`
+    ///
+    /// The keywords `a`, `has`, `is` and `of` may be prefixed by `@`. Whether
+    /// they may be used without `@` depends on the `@keywords` directive.
+    ///
+    /// # Result
+    ///
+    /// Returns the predicate and whether subject and object are swapped,
+    /// i.e. for `<=`, `is ... of` and `<-`.
+    pub fn predicate(&mut self, i: &'doc str) -> PResult<'doc, (MownTerm<'doc>, bool)> {
+        match self.expression(i) {
+            Err(NomError::Error(_)) => {}
+            res => return res.map(|(rest, p)| (rest, (p, false))),
+        };
+
+        if let Ok((rest, sugar)) = alt((tag("=>"), tag("<="), tag("=")))(i).map_pr() {
+            let p: MownTerm<'doc> = match sugar {
+                "=" => owl::iri::sameAs.clone_into().into(),
+                _ => log::iri::implies.clone_into().into(),
+            };
+            return Ok((rest, (p, sugar == "<=")));
+        }
+
+        if let Ok((rest, _)) = self.keyword(i, "a") {
+            let p: MownTerm<'doc> = rdf::iri::type_.clone_into().into();
+            return Ok((rest, (p, false)));
+        }
 
-/// Parses rule `iri`
-pub fn iri<'a>(i: &'a str, ctx: &RefContext<'a>) -> IResult<&'a str, CowTerm<'a>> {
-    alt((
-        map_opt(parse_regex(&ttl_terminal::IRIREF), |s| {
-            if s.len() < 2 {
-                None
+        if let Ok((rest, _)) = self.keyword(i, "has") {
+            let (rest, _) = multispace0(rest).map_pr()?;
+            let (rest, p) = self
+                .expression(rest)
+                .cut_expecting("an expression after `has`")?;
+            return Ok((rest, (p, false)));
+        }
+
+        if let Ok((rest, _)) = tag("<-")(i).map_pr() {
+            let (rest, _) = multispace0(rest).map_pr()?;
+            let (rest, p) = self
+                .expression(rest)
+                .cut_expecting("an expression after `<-`")?;
+            return Ok((rest, (p, true)));
+        }
+
+        let (rest, _) = self.keyword(i, "is")?;
+        let (rest, _) = multispace0(rest).map_pr()?;
+        let (rest, p) = self
+            .expression(rest)
+            .cut_expecting("an expression after `is`")?;
+        let (rest, _) = multispace0(rest).map_pr()?;
+        let (rest, _) = self
+            .keyword(rest, "of")
+            .cut_expecting("`of` after `is ...`")?;
+        Ok((rest, (p, true)))
+    }
+
+    /// Parses N3's production
+    /// `object ::= expression`
+    pub fn object(&mut self, i: &'doc str) -> PResult<'doc, MownTerm<'doc>> {
+        self.expression(i)
+    }
+
+    /// Parses N3's production
+    /// `expression ::= path_item (('!' | '^') path_item)*`
+    ///
+    /// # Result
+    ///
+    /// Returns the node the path points to. Each step of a path introduces a
+    /// new existential `e`. For a forward path `a!p` the triple `a p e` is
+    /// pushed onto the context's stack, for a backward path `a^p` the triple
+    /// `e p a`.
+    pub fn expression(&mut self, i: &'doc str) -> PResult<'doc, MownTerm<'doc>> {
+        let (mut rest, mut node) = self.path_item(i)?;
+        let mut triples = vec![];
+
+        loop {
+            let (r, _) = multispace0(rest).map_pr()?;
+            let (r, step) = match alt((tag("!"), tag("^")))(r).map_pr() {
+                Ok(res) => res,
+                Err(_) => break,
+            };
+            let (r, _) = multispace0(r).map_pr()?;
+            let (r, p) = self
+                .path_item(r)
+                .cut_expecting("an expression after `!` or `^`")?;
+
+            let e: MownTerm<'doc> = self.ctx.new_anon_bnode().into();
+            if step == "!" {
+                triples.push([node, p, e.clone()]);
             } else {
-                Some(ctx.borrow().new_iri(unwrap_str(s, 1)))
+                triples.push([e.clone(), p, node]);
+            }
+            node = e;
+            rest = r;
+        }
+
+        self.ctx.push_triples(triples.into_iter());
+        Ok((rest, node))
+    }
+
+    /// Parses N3's production
+    /// `path_item ::= symbol | blank_node | variable | literal | formula | list | bnode_property_list`
+    pub fn path_item(&mut self, i: &'doc str) -> PResult<'doc, MownTerm<'doc>> {
+        self.alt(
+            i,
+            &[
+                &|p: &mut Self, i| p.symbol(i).map(|(rest, iri)| (rest, p.quantified(iri))),
+                &|p: &mut Self, i| p.blank_node(i).map(|(rest, bn)| (rest, bn.into())),
+                &|p: &mut Self, i| p.variable(i).map(|(rest, v)| (rest, v.into())),
+                &|p: &mut Self, i| p.literal(i).map(|(rest, lit)| (rest, lit.into())),
+                &|p: &mut Self, i| p.formula(i).map(|(rest, f)| (rest, f.into())),
+                &Self::list,
+                &Self::bnode_property_list,
+            ],
+        )
+    }
+
+    /// Parses N3's production
+    /// `list ::= '(' expression* ')'`
+    ///
+    /// # Result
+    ///
+    /// Returns the subject of the first element in the list. If the list is
+    /// empty `rdf:nil` is returned. The `rdf:first` and `rdf:rest` triples of
+    /// the list are pushed onto the context's stack.
+    pub fn list(&mut self, i: &'doc str) -> PResult<'doc, MownTerm<'doc>> {
+        let (mut rest, _) = tag("(")(i).map_pr()?;
+        let mut elements = vec![];
+
+        loop {
+            let (r, _) = multispace0(rest).map_pr()?;
+            if let Ok((r, _)) = tag(")")(r).map_pr() {
+                rest = r;
+                break;
+            }
+            let (r, e) = self
+                .expression(r)
+                .cut_expecting("an expression or `)` in the list")?;
+            elements.push(e);
+            rest = r;
+        }
+
+        let first: MownTerm<'doc> = rdf::iri::first.clone_into().into();
+        let next: MownTerm<'doc> = rdf::iri::rest.clone_into().into();
+        let mut head: MownTerm<'doc> = rdf::iri::nil.clone_into().into();
+        for e in elements.into_iter().rev() {
+            let node: MownTerm<'doc> = self.ctx.new_anon_bnode().into();
+            self.ctx.push_triple([node.clone(), first.clone(), e]);
+            self.ctx.push_triple([node.clone(), next.clone(), head]);
+            head = node;
+        }
+
+        Ok((rest, head))
+    }
+
+    /// Parses N3's production
+    /// `formula ::= '{' ( statement ('.' statement)* )? '}'`
+    ///
+    /// The `.` after the last statement is optional.
+    pub fn formula(&mut self, i: &'doc str) -> PResult<'doc, MownFormula<'doc>> {
+        let (rest, _) = tag("{")(i).map_pr()?;
+
+        let outer = mem::take(&mut self.ctx.triple_stack);
+        // directives within the formula do not apply outside
+        let prolog = self.ctx.prolog.clone();
+        let keywords = self.bare_keywords.clone();
+        self.quantifiers.push(HashMap::new());
+        let res = self.formula_content(rest);
+        self.quantifiers.pop();
+        self.ctx.prolog = prolog;
+        self.bare_keywords = keywords;
+        let inner = mem::replace(&mut self.ctx.triple_stack, outer);
+        let (rest, _) = res?;
+
+        Ok((rest, inner.into_iter().collect::<Vec<_>>().into()))
+    }
+
+    /// Parses the statements of a formula including the closing `}`.
+    ///
+    /// All parsed triples are pushed onto the context's stack.
+    fn formula_content(&mut self, i: &'doc str) -> PResult<'doc, ()> {
+        let mut rest = i;
+
+        loop {
+            let (r, _) = multispace0(rest).map_pr()?;
+            if let Ok((r, _)) = tag("}")(r).map_pr() {
+                return Ok((r, ()));
+            }
+
+            // directives contain their own `.`
+            match self.directive(r) {
+                Ok((r, _)) => {
+                    rest = r;
+                    continue;
+                }
+                Err(NomError::Error(_)) => {}
+                Err(e) => return Err(e),
             }
-        }),
-        |i| prefixed_name(i, ctx),
-    ))(i)
+
+            let (r, _) = self
+                .alt(r, &[&Self::declaration, &Self::simple_statement])
+                .cut_expecting("a statement or `}` in the formula")?;
+            let (r, _) = multispace0(r).map_pr()?;
+            if let Ok((r, _)) = tag("}")(r).map_pr() {
+                return Ok((r, ()));
+            }
+            let (r, _) = tag(".")(r)
+                .map_pr()
+                .cut_expecting("`.` or `}` after the statement")?;
+            rest = r;
+        }
+    }
+
+    /// Parses N3's production
+    /// `variable ::= '?' VARNAME`
+    pub fn variable(&mut self, i: &'doc str) -> PResult<'doc, Variable<MownStr<'doc>>> {
+        let (rest, name) = variable(i).map_pr()?;
+        Ok((rest, Variable::new_unchecked(name)))
+    }
+
+    /// Parses N3's production
+    /// `symbol ::= iri | BARENAME`
+    pub fn symbol(&mut self, i: &'doc str) -> PResult<'doc, Iri<MownStr<'doc>>> {
+        self.alt(i, &[&Self::iri, &Self::bare_name])
+    }
+
+    /// Parses a bare name and resolves it against the default prefix.
+    ///
+    /// Bare names are only valid after a `@keywords` directive. Bare keywords
+    /// and the boolean literals are not considered as bare names.
+    pub fn bare_name(&mut self, i: &'doc str) -> PResult<'doc, Iri<MownStr<'doc>>> {
+        if self.bare_keywords.is_none() {
+            return Err(PosError::err(i, Error::NoMatch));
+        }
+        let (rest, name) = parse_regex(&BARENAME)(i).map_pr()?;
+        if self.is_bare_keyword(name) || name == "true" || name == "false" || rest.starts_with(':')
+        {
+            return Err(PosError::err(i, Error::NoMatch));
+        }
+
+        let (_, ns) = self.ctx.prolog.prefixes.get("").or_into_pr(
+            i,
+            Error::InvalidPrefix(String::new()),
+            rest,
+        )?;
+        ns.get_iri(MownStr::from(name)).into_pr(i, rest)
+    }
+
+    /// Parses the keyword `kw` with a leading `@`, or without if `kw` may be
+    /// used bare.
+    fn keyword(&self, i: &'doc str, kw: &'static str) -> PResult<'doc, ()> {
+        if let Ok((rest, _)) = preceded(tag("@"), keyword(kw))(i).map_pr() {
+            return Ok((rest, ()));
+        }
+        if self.is_bare_keyword(kw) {
+            let (rest, _) = keyword(kw)(i).map_pr()?;
+            return Ok((rest, ()));
+        }
+        Err(PosError::err(i, Error::NoMatch))
+    }
+
+    /// Checks if `kw` may be used without `@`.
+    ///
+    /// Without a `@keywords` directive these are `a`, `has`, `is` and `of`.
+    fn is_bare_keyword(&self, kw: &str) -> bool {
+        match &self.bare_keywords {
+            Some(keywords) => keywords.contains(kw),
+            None => DEFAULT_KEYWORDS.contains(&kw),
+        }
+    }
+
+    /// Adds the IRI as universal or existential to the current scope.
+    ///
+    /// Universals are named by the local name of the IRI if possible.
+    /// Existentials get a new anonymous blank node.
+    fn declare(&mut self, iri: Iri<MownStr<'doc>>, universal: bool) {
+        let key = iri.value().to_string();
+        let term: MownTerm<'doc> = if universal {
+            Variable::new_unchecked(self.universal_name(&key)).into()
+        } else {
+            self.ctx.new_anon_bnode().into()
+        };
+        self.quantifiers
+            .last_mut()
+            .expect("The scope of the document is never removed")
+            .insert(key, term);
+    }
+
+    /// Returns an unused name for a universal declared for `iri`.
+    fn universal_name(&mut self, iri: &str) -> String {
+        let local = iri
+            .rsplit(|c: char| c == '#' || c == '/')
+            .next()
+            .unwrap_or("");
+        let base = match VARNAME.find(local) {
+            Some(m) if m.end() == local.len() => local,
+            _ => "var",
+        };
+
+        let mut name = base.to_string();
+        let mut cnt = 0;
+        while self.universals.contains(&name) {
+            cnt += 1;
+            name = format!("{}_{}", base, cnt);
+        }
+        self.universals.insert(name.clone());
+        name
+    }
+
+    /// Returns the quantified term if `iri` was declared in the current or an
+    /// enclosing scope. Otherwise, `iri` is returned as it is.
+    fn quantified(&self, iri: Iri<MownStr<'doc>>) -> MownTerm<'doc> {
+        if self.quantifiers.iter().all(HashMap::is_empty) {
+            return iri.into();
+        }
+
+        let key = iri.value();
+        self.quantifiers
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&*key))
+            .cloned()
+            .unwrap_or_else(|| iri.into())
+    }
+
+    /// Parses N3's production
+    /// `bnode_property_list ::= '[' property_list ']'`
+    ///
+    /// # Result
+    ///
+    /// Returns the new blank node. The triples of the property list are
+    /// pushed onto the context's stack.
+    pub fn bnode_property_list(&mut self, i: &'doc str) -> PResult<'doc, MownTerm<'doc>> {
+        let (rest, _) = tag("[")(i).map_pr()?;
+        let (rest, _) = multispace0(rest).map_pr()?;
+        let bn: MownTerm<'doc> = self.ctx.new_anon_bnode().into();
+        let (rest, _) = self
+            .property_list(rest, &bn)
+            .cut_expecting("a predicate in `[ ... ]`")?;
+        let (rest, _) = multispace0(rest).map_pr()?;
+        let (rest, _) = tag("]")(rest)
+            .map_pr()
+            .cut_expecting("`]` to close the blank node property list")?;
+
+        Ok((rest, bn))
+    }
 }
 
-/// Parses rule `prefixed_name`
-pub fn prefixed_name<'a>(i: &'a str, ctx: &RefContext<'a>) -> IResult<&'a str, CowTerm<'a>> {
-    if let Ok((rest, parsed)) = ttl_terminal::pname_ln(i) {
-        let mut parts = parsed.split(':');
-        let (ns, suffix) = (parts.next().unwrap(), parts.next().unwrap());
-        // TODO: introduce proper error handling
-        let ctx = ctx.borrow();
-        let ns = ctx
+// SPARQL rules.
+//
+// Copied from the Turtle parser.
+impl<'doc> Parser<'doc> {
+    /// Parses SPARQL's production and sets the new base IRI accordingly
+    /// (resolve new IRI against old base).
+    ///
+    /// [5s] sparqlBase ::= "BASE" IRIREF
+    pub fn sparql_base(&mut self, i: &'doc str) -> PResult<'doc, ()> {
+        let (rest, base) = sparql_base(i).map_pr()?;
+        let (_, base) = numeric_escape(base).into_pr(i, rest)?;
+        let (_, base) = Iri::new(base).into_pr(i, rest)?;
+        self.ctx
             .prolog
-            .prefixes
-            .get(ns)
-            .ok_or_else(|| NomError::Error(error_position!(i, ErrorKind::Verify)))?;
-        return Ok((rest, unsafe {
-            Term::new_iri2_unchecked(ns.clone(), ttl_production::escape(suffix), None).into()
-        }));
-    };
-
-    map_opt(parse_regex(&ttl_terminal::PNAME_NS), |s| {
-        let ns = &s[..s.len() - 1]; // last char is ':'
-        ctx.borrow()
+            .to_mut()
+            .set_base(base)
+            .map(|_| ())
+            .into_pr(i, rest)
+    }
+
+    /// Parses SPARQL's production and adds the new prefix to the parser's
+    /// context.
+    ///
+    /// [6s] sparqlPrefix ::= "PREFIX" PNAME_NS IRIREF
+    pub fn sparql_prefix(&mut self, i: &'doc str) -> PResult<'doc, ()> {
+        let (rest, (prefix, ns)) = sparql_prefix(i).map_pr()?;
+        let (_, ns) = numeric_escape(ns).into_pr(i, rest)?;
+        self.ctx
             .prolog
-            .prefixes
-            .get(ns)
-            .map(|ns| unsafe { Term::new_iri_unchecked(ns.clone(), None).into() })
-    })(i)
+            .to_mut()
+            .add_prefix(prefix, ns)
+            .map(|_| ())
+            .map_err(|_| Error::InvalidPrefix(prefix.to_string()))
+            .into_pr(i, rest)
+    }
 }
 
-/// Parses rule `formula`
-pub fn formula<'a>(i: &'a str, ctx: &RefContext<'a>) -> IResult<&'a str, CowFormula<'a>> {
-    tuple((
-        tag("{"),
-        ttl_terminal::multispace0,
-        opt(tuple((
-            |i| statement(i, ctx),
-            ttl_terminal::multispace0,
-            opt(separated_list(
-                tuple((
-                    ttl_terminal::multispace0,
-                    tag("."),
-                    ttl_terminal::multispace0,
-                )),
-                |i| statement(i, ctx),
-            )),
-        ))),
-        ttl_terminal::multispace0,
-        tag("}"),
-    ))(i)
-    .map(|(rest, (_, _, statements, _, _))| match statements {
-        Some((mut first, _, others)) => match others {
-            Some(others) => {
-                first.extend(others.into_iter().flatten());
-                (rest, first.into())
-            }
-            None => (rest, first.into()),
-        },
-        None => (rest, Formula::default()),
-    })
-}
+// Common rules of N3-derivatives.
+//
+// Copied from the Turtle parser.
+impl<'doc> Parser<'doc> {
+    /// Parses Turtle's production and sets the new base IRI accordingly
+    /// (resolve new IRI against old base).
+    ///
+    /// [5] base ::= '@base' IRIREF '.'
+    pub fn base(&mut self, i: &'doc str) -> PResult<'doc, ()> {
+        let (rest, base) = base(i).map_pr()?;
+        let (_, base) = numeric_escape(base).into_pr(i, rest)?;
+        let (_, base) = Iri::new(base).into_pr(i, rest)?;
+        self.ctx
+            .prolog
+            .to_mut()
+            .set_base(base)
+            .map(|_| ())
+            .into_pr(i, rest)?;
+        Ok((rest, ()))
+    }
 
-/// Parses rule `variable`
-pub fn variable<'a>(i: &'a str) -> IResult<&'a str, CowTerm<'a>> {
-    // cut the leading '?'
-    map(parse_regex(&VARIABLE), |s| {
-        unsafe { Term::new_variable_unchecked(&s[1..]) }.into()
-    })(i)
-}
+    /// Parses Turtle's production and adds the new prefix to the parser's
+    /// context.
+    ///
+    /// [4] prefixID ::= '@prefix' PNAME_NS IRIREF '.'
+    pub fn prefix(&mut self, i: &'doc str) -> PResult<'doc, ()> {
+        let (rest, (prefix, ns)) = prefix(i).map_pr()?;
+        let (_, ns) = numeric_escape(ns).into_pr(i, rest)?;
+        self.ctx
+            .prolog
+            .to_mut()
+            .add_prefix(prefix, ns)
+            .map(|_| ())
+            .map_err(|_| Error::InvalidPrefix(prefix.to_string()))
+            .into_pr(i, rest)
+    }
 
-/// Parses rule `literal`
-pub fn literal<'a>(i: &'a str, ctx: &RefContext<'a>) -> IResult<&'a str, CowTerm<'a>> {
-    alt((
-        |i| rdf_literal(i, ctx),
-        |i| numeric_literal(i),
-        |i| boolean_literal(i),
-    ))(i)
-}
+    /// Parses Turtle's production
+    /// [13] literal ::= RDFLiteral | NumericLiteral | BooleanLiteral
+    pub fn literal(&mut self, i: &'doc str) -> PResult<'doc, Literal<MownStr<'doc>>> {
+        self.alt(
+            i,
+            &[
+                &Self::rdf_literal,
+                &Self::numeric_literal,
+                &Self::boolean_literal,
+            ],
+        )
+    }
+
+    /// Parses Turtle's production
+    /// [16] NumericLiteral ::= INTEGER | DECIMAL | DOUBLE
+    ///
+    /// The terminals are tried in reverse order as `INTEGER` would match the
+    /// leading digits of a `DECIMAL` or `DOUBLE`.
+    pub fn numeric_literal(&mut self, i: &'doc str) -> PResult<'doc, Literal<MownStr<'doc>>> {
+        alt((
+            map(parse_regex(&DOUBLE), |txt| {
+                Literal::new_dt(txt, xsd::iri::double)
+            }),
+            map(parse_regex(&DECIMAL), |txt| {
+                Literal::new_dt(txt, xsd::iri::decimal)
+            }),
+            map(parse_regex(&INTEGER), |txt| {
+                Literal::new_dt(txt, xsd::iri::integer)
+            }),
+        ))(i)
+        .map_pr()
+    }
+
+    /// Parses Turtle's production
+    /// [128s] RDFLiteral ::= String (LANGTAG | '^^' iri)?
+    pub fn rdf_literal(&mut self, i: &'doc str) -> PResult<'doc, Literal<MownStr<'doc>>> {
+        let (rest, txt) = lexical_value(i).map_pr()?;
+        let (_, txt) = string_escape(txt).into_pr(i, rest)?;
+
+        if let Ok((rest, _)) = tag("^^")(rest).map_pr() {
+            let (rest, dt) = self.iri(rest).cut_expecting("a datatype IRI after `^^`")?;
+            Ok((rest, Literal::new_dt(txt, dt)))
+        } else if let Ok((rest, lang)) = parse_regex(&LANGTAG)(rest) {
+            // cut the leading '@'
+            Literal::new_lang(txt, &lang[1..]).into_pr(i, rest)
+        } else {
+            Ok((rest, Literal::new_dt(txt, xsd::iri::string)))
+        }
+    }
+
+    /// Parses Turtle's production
+    /// [133s] BooleanLiteral ::= 'true' | 'false'
+    pub fn boolean_literal(&mut self, i: &'doc str) -> PResult<'doc, Literal<MownStr<'doc>>> {
+        let (rest, lexical) = boolean_literal(i).map_pr()?;
+        Ok((rest, Literal::new_dt(lexical, xsd::iri::boolean)))
+    }
 
-/// Parses rule `rdf_literal`
-pub fn rdf_literal<'a>(i: &'a str, ctx: &RefContext<'a>) -> IResult<&'a str, CowTerm<'a>> {
-    let (rest, string) = string(i)?;
-
-    if let Ok((rest, N3Term::Term(dt))) = preceded(tag("^^"), |i| iri(i, ctx))(rest) {
-        Ok((rest, Term::new_literal_dt_unchecked(string, dt).into()))
-    } else if let Ok((rest, lang)) = parse_regex(&ttl_terminal::LANGTAG)(rest) {
-        let term = Term::new_literal_lang(string, &lang[1..]) // cut the '@'
-            .map(Into::into)
-            .map_err(|_| NomError::Error(error_position!(rest, ErrorKind::Verify)))?;
-        Ok((rest, term))
-    } else {
-        Ok((
+    /// Parses Turtle's production
+    /// [135s] iri ::= IRIREF | PrefixedName
+    pub fn iri(&mut self, i: &'doc str) -> PResult<'doc, Iri<MownStr<'doc>>> {
+        self.alt(i, &[&Self::iriref, &Self::prefixed_name])
+    }
+
+    /// Parses Turtle's terminal `IRIREF` into an `Iri`.
+    /// [18] IRIREF ::= '<' ([^#x00-#x20<>"{}|^`\] | UCHAR)* '>'
+    pub fn iriref(&mut self, i: &'doc str) -> PResult<'doc, Iri<MownStr<'doc>>> {
+        let (rest, iri) = iriref(i).map_pr()?;
+        let (_, iri) = numeric_escape(iri).into_pr(i, rest)?;
+        Ok((rest, self.ctx.new_iri(iri)))
+    }
+
+    /// Parses Turtle's production
+    /// [136s] PrefixedName ::= PNAME_LN | PNAME_NS
+    pub fn prefixed_name(&mut self, i: &'doc str) -> PResult<'doc, Iri<MownStr<'doc>>> {
+        let (rest, (ns, suffix)) =
+            alt((pname_ln_split, map(pname_ns, |s| (s, None))))(i).map_pr()?;
+        let (_, ns) = self.ctx.prolog.prefixes.get(ns).or_into_pr(
+            i,
+            Error::InvalidPrefix(ns.to_string()),
             rest,
-            Term::new_literal_dt_unchecked(string, Term::from(&xsd::string)).into(),
-        ))
+        )?;
+
+        if let Some(suffix) = suffix {
+            ns.get_iri(local_escape(suffix)).into_pr(i, rest)
+        } else {
+            Ok((rest, ns.clone().into()))
+        }
     }
-}
 
-/// Parses rule `numeric_literal`
-pub fn numeric_literal<'a>(i: &'a str) -> IResult<&'a str, CowTerm<'a>> {
-    ttl_production::numeric_literal(i).map(|(rest, t)| (rest, t.into()))
-}
+    /// Parses Turtle's production
+    /// [137s] BlankNode ::= BLANK_NODE_LABEL | ANON
+    pub fn blank_node(&mut self, i: &'doc str) -> PResult<'doc, BlankNode<MownStr<'doc>>> {
+        let (rest, id) = alt((blank_node_label, anon))(i).map_pr()?;
+
+        let bn = match id {
+            Some(id) => self.ctx.new_labeled_bnode(&id[2..]),
+            None => self.ctx.new_anon_bnode(),
+        };
 
-/// Parses rule `boolean_literal`
-pub fn boolean_literal<'a>(i: &'a str) -> IResult<&'a str, CowTerm<'a>> {
-    ttl_production::boolean_literal(i).map(|(rest, t)| (rest, t.into()))
+        Ok((rest, bn))
+    }
+
+    /// Like `nom::combinator::alt` but allows `&mut self`.
+    fn alt<O>(
+        &mut self,
+        i: &'doc str,
+        parsers: &[&dyn Fn(&mut Self, &'doc str) -> PResult<'doc, O>],
+    ) -> PResult<'doc, O> {
+        for p in parsers {
+            match p(self, i) {
+                Err(NomError::Error(_)) => {}
+                res => return res,
+            };
+        }
+        Err(PosError::err(i, Error::NoMatch))
+    }
 }
 
-/// Parses rule `string`
-pub fn string<'a>(i: &'a str) -> IResult<&'a str, Cow<'a, str>> {
-    ttl_production::string(i)
+/// Parses the keyword `kw` if it is not followed by further characters of a
+/// name.
+fn keyword<'a>(kw: &'static str) -> impl Fn(&'a str) -> IResult<&'a str, &'a str> {
+    move |i| {
+        let (rest, kw) = tag(kw)(i)?;
+        match rest.chars().next() {
+            Some(c) if c.is_alphanumeric() || c == '_' || c == '-' || c == ':' => {
+                Err(NomError::Error((i, ErrorKind::Tag)))
+            }
+            _ => Ok((rest, kw)),
+        }
+    }
 }
 
-/// Parses rule `bnode_property_list`
+/// Returns the name of the variable without the leading `?`.
 ///
-/// Returns the node representing the parsed anonymous blank node and the
-/// triples contained are pushed to the context's stack.
-pub fn bnode_property_list<'a>(
-    i: &'a str,
-    ctx: &RefContext<'a>,
-) -> IResult<&'a str, (CowTerm<'a>, N3TriplesIter<'a>)> {
-    let (rest, _) = tag("[")(i)?;
-    let (rest, _) = ttl_terminal::multispace0(rest)?;
-    let node = ctx.borrow_mut().new_anon_bnode();
-    let (rest, triples) = property_list(rest, node.clone(), ctx)?;
-    let (rest, _) = ttl_terminal::multispace0(rest)?;
-    let (rest, _) = tag("]")(rest)?;
-
-    Ok((rest, (node, triples)))
+/// `variable ::= '?' VARNAME`
+fn variable(i: &str) -> IResult<&str, &str> {
+    parse_regex(&VARIABLE)(i).map(|(rest, var)| (rest, &var[1..]))
 }
 
-/// Parses rule `blank_node`
-pub fn blank_node<'a>(i: &'a str, ctx: &RefContext<'a>) -> IResult<&'a str, CowTerm<'a>> {
-    alt((
-        // ok because validity of label is checked.
-        map(ttl_terminal::blank_node_label, |s| {
-            ctx.borrow_mut().new_labeled_bnode(&s[2..]) // skip the parsed `_:`
-        }),
-        map(parse_regex(&ttl_terminal::ANON), |_| {
-            ctx.borrow_mut().new_anon_bnode()
-        }),
-    ))(i)
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::n3::N3Term;
+    use test_case::test_case;
+
+    fn parser(doc: &str) -> Parser<'_> {
+        let mut parser = Parser::new(doc);
+        parser.ctx.prolog.to_mut().add_default_prefixes();
+        parser
+            .ctx
+            .prolog
+            .to_mut()
+            .add_prefix("", "http://example.org/".into())
+            .unwrap();
+        parser
+    }
+
+    #[test_case("?x" => Some("x".to_string()) ; "variable")]
+    #[test_case("?x_1 rest" => Some("x_1".to_string()) ; "with rest")]
+    #[test_case("x" => None ; "no question mark")]
+    fn check_variable(i: &str) -> Option<String> {
+        parser(i)
+            .variable(i)
+            .ok()
+            .map(|(_, v)| v.value().to_string())
+    }
+
+    #[test_case("a" => ("http://www.w3.org/1999/02/22-rdf-syntax-ns#type".to_string(), false) ; "a")]
+    #[test_case("@a" => ("http://www.w3.org/1999/02/22-rdf-syntax-ns#type".to_string(), false) ; "at a")]
+    #[test_case("=" => ("http://www.w3.org/2002/07/owl#sameAs".to_string(), false) ; "same as")]
+    #[test_case("=>" => ("http://www.w3.org/2000/10/swap/log#implies".to_string(), false) ; "implies")]
+    #[test_case("<=" => ("http://www.w3.org/2000/10/swap/log#implies".to_string(), true) ; "implied by")]
+    #[test_case(":p" => ("http://example.org/p".to_string(), false) ; "prefixed")]
+    #[test_case("has :p" => ("http://example.org/p".to_string(), false) ; "has")]
+    #[test_case("@has :p" => ("http://example.org/p".to_string(), false) ; "at has")]
+    #[test_case("is :p of" => ("http://example.org/p".to_string(), true) ; "is of")]
+    #[test_case("@is :p @of" => ("http://example.org/p".to_string(), true) ; "at is of")]
+    #[test_case("<- :p" => ("http://example.org/p".to_string(), true) ; "inverse")]
+    fn check_predicate(i: &str) -> (String, bool) {
+        match parser(i).predicate(i) {
+            Ok(("", (N3Term::Iri(iri), inverse))) => (iri.value().to_string(), inverse),
+            res => panic!("Expected IRI, got {:?}", res),
+        }
+    }
+
+    #[test_case("is :p" ; "missing of")]
+    #[test_case("has ." ; "missing predicate")]
+    #[test_case("<- ;" ; "missing inverse predicate")]
+    fn check_predicate_invalid(i: &str) {
+        assert!(matches!(parser(i).predicate(i), Err(NomError::Failure(_))));
+    }
+
+    #[test_case("@keywords a, is, of ." => vec!["a", "is", "of"] ; "list")]
+    #[test_case("@keywords." => Vec::<&str>::new() ; "empty")]
+    fn check_keywords(i: &str) -> Vec<&str> {
+        let mut parser = parser(i);
+        let (rest, _) = parser.keywords(i).unwrap();
+        assert_eq!(rest, "");
+        let mut keywords: Vec<_> = parser.bare_keywords.unwrap().into_iter().collect();
+        keywords.sort();
+        keywords
+    }
+
+    #[test_case("a", "a" => true ; "default a")]
+    #[test_case("@a", "a" => true ; "default at a")]
+    #[test_case("forAll", "forAll" => false ; "default forAll")]
+    #[test_case("@keywords a, forAll . forAll", "forAll" => true ; "declared forAll")]
+    #[test_case("@keywords is . a", "a" => false ; "undeclared a")]
+    #[test_case("@keywords is . @a", "a" => true ; "undeclared at a")]
+    fn check_keyword(i: &str, kw: &'static str) -> bool {
+        let mut parser = parser(i);
+        let i = match parser.keywords(i) {
+            Ok((rest, _)) => &rest[1..],
+            Err(_) => i,
+        };
+        parser.keyword(i, kw).is_ok()
+    }
+
+    #[test]
+    fn check_bare_name() {
+        let i = "@keywords a . x a y ; is of, true .";
+        let mut parser = parser(i);
+        // bare names are not allowed without `@keywords`
+        assert!(parser.bare_name("x").is_err());
+
+        let (rest, _) = parser.statement(i).unwrap();
+        let (rest, _) = parser.statement(&rest[1..]).unwrap();
+        assert_eq!(rest, "");
+        let triples: Vec<_> = parser.ctx.triple_stack.iter().collect();
+        assert_eq!(triples.len(), 3);
+        let iri = |t: &MownTerm| match t {
+            N3Term::Iri(iri) => iri.value().to_string(),
+            t => panic!("Expected an IRI but got {:?}", t),
+        };
+        assert_eq!(iri(&triples[0][0]), "http://example.org/x");
+        assert_eq!(
+            iri(&triples[0][1]),
+            "http://www.w3.org/1999/02/22-rdf-syntax-ns#type"
+        );
+        assert_eq!(iri(&triples[0][2]), "http://example.org/y");
+        // `is` and `of` are no keywords anymore
+        assert_eq!(iri(&triples[1][1]), "http://example.org/is");
+        assert_eq!(iri(&triples[1][2]), "http://example.org/of");
+        assert!(matches!(&triples[2][2], N3Term::Literal(_)));
+    }
+
+    #[test_case("ab" ; "longer name")]
+    #[test_case("a:b" ; "prefixed name")]
+    fn check_keyword_boundary(i: &str) {
+        assert!(keyword("a")(i).is_err());
+    }
+
+    #[test]
+    fn check_inverse_property_list() {
+        let i = ":s is :p of :o1, :o2 ; <= :o3 ; = :o4";
+        let mut parser = parser(i);
+        let s: MownTerm = parser.iri(":s").unwrap().1.into();
+        parser.property_list(&i[3..], &s).unwrap();
+        let triples: Vec<_> = parser.ctx.triple_stack.iter().collect();
+        assert_eq!(triples.len(), 4);
+        assert_eq!(triples[0][2], s);
+        assert_eq!(triples[1][2], s);
+        assert_eq!(triples[2][2], s);
+        assert_eq!(triples[3][0], s);
+    }
+
+    #[test_case("{}" => 0 ; "empty")]
+    #[test_case("{ :s :p :o }" => 1 ; "without dot")]
+    #[test_case("{ :s :p :o . }" => 1 ; "with dot")]
+    #[test_case("{ :s :p :o, :o2 ; :q ( 1 ) . [ :p :o ] }" => 6 ; "multiple statements")]
+    #[test_case("{ :s :p { :s :p :o } }" => 1 ; "nested")]
+    fn check_formula(i: &str) -> usize {
+        let mut parser = parser(i);
+        let (rest, f) = parser.formula(i).unwrap();
+        assert_eq!(rest, "");
+        // the outer stack is not touched
+        assert!(parser.ctx.triple_stack.is_empty());
+        f.len()
+    }
+
+    #[test_case("{ :s :p :o" ; "not closed")]
+    #[test_case("{ :s :p :o :o2 }" ; "missing dot")]
+    #[test_case("{ :s :p }" ; "missing object")]
+    fn check_formula_invalid(i: &str) {
+        assert!(matches!(parser(i).formula(i), Err(NomError::Failure(_))));
+    }
+
+    #[test_case("@forAll :x ." => (1, 0) ; "universal")]
+    #[test_case("@forSome :x ." => (0, 1) ; "existential")]
+    #[test_case("@forAll :x, :y ; :z ." => (2, 0) ; "multiple")]
+    #[test_case("@forAll ." => (0, 0) ; "empty")]
+    fn check_declaration(i: &str) -> (usize, usize) {
+        let mut parser = parser(i);
+        let (rest, _) = parser.declaration(i).unwrap();
+        assert!(rest.starts_with(' '));
+        let scope = &parser.quantifiers[0];
+        let universals = scope
+            .values()
+            .filter(|t| matches!(t, N3Term::Universal(_)))
+            .count();
+        (universals, scope.len() - universals)
+    }
+
+    #[test]
+    fn check_declaration_scope() {
+        let i = "{ @forAll :x . :x :p :y . { @forSome :y . :x :p :y } } ";
+        let mut parser = parser(i);
+        let (_, f) = parser.formula(i).unwrap();
+        // declarations do not leak out of the formula
+        assert_eq!(parser.quantifiers.len(), 1);
+        assert!(parser.quantifiers[0].is_empty());
+
+        assert!(matches!(&f[0][0], N3Term::Universal(v) if &*v.value() == "x"));
+        assert!(matches!(&f[0][2], N3Term::Iri(_)));
+        let inner = match &f[1][2] {
+            N3Term::Formula(inner) => inner,
+            t => panic!("Expected a formula but got {:?}", t),
+        };
+        assert_eq!(inner[0][0], f[0][0]);
+        assert!(matches!(&inner[0][2], N3Term::Existential(_)));
+    }
+
+    #[test_case("http://example.org/x" => "x" ; "slash")]
+    #[test_case("http://example.org/#x_1" => "x_1" ; "hash")]
+    #[test_case("http://example.org/x-y" => "var" ; "invalid name")]
+    #[test_case("http://example.org/" => "var" ; "empty name")]
+    fn check_universal_name(iri: &str) -> String {
+        parser("").universal_name(iri)
+    }
+
+    #[test]
+    fn check_universal_name_unique() {
+        let mut parser = parser("");
+        assert_eq!(parser.universal_name("http://example.org/x"), "x");
+        assert_eq!(parser.universal_name("http://example.com/x"), "x_1");
+        assert_eq!(parser.universal_name("http://example.net/x"), "x_2");
+    }
+
+    #[test]
+    fn check_path() {
+        let i = ":joe!:mother!:office^:worksIn rest";
+        let mut parser = parser(i);
+        let (rest, node) = parser.expression(i).unwrap();
+        assert_eq!(rest, " rest");
+        assert!(matches!(node, N3Term::Existential(_)));
+
+        let triples: Vec<_> = parser.ctx.triple_stack.iter().collect();
+        assert_eq!(triples.len(), 3);
+        assert!(
+            matches!(&triples[0][0], N3Term::Iri(iri) if &*iri.value() == "http://example.org/joe")
+        );
+        assert_eq!(triples[0][2], triples[1][0]);
+        // backward step
+        assert_eq!(triples[1][2], triples[2][2]);
+        assert_eq!(triples[2][0], node);
+    }
+
+    #[test_case(":a!" ; "missing item")]
+    #[test_case(":a^ ." ; "missing backward item")]
+    fn check_path_invalid(i: &str) {
+        assert!(matches!(parser(i).expression(i), Err(NomError::Failure(_))));
+    }
+
+    #[test_case("( )" => 0 ; "empty")]
+    #[test_case("( 1 ?x { :s :p :o } )" => 6 ; "mixed")]
+    #[test_case("( ( 1 ) [ :p :o ] )" => 7 ; "nested")]
+    fn check_list(i: &str) -> usize {
+        let mut parser = parser(i);
+        parser.list(i).unwrap();
+        parser.ctx.triple_stack.len()
+    }
+
+    #[test]
+    fn check_expression_terms() {
+        let check = |i: &str| {
+            let (_, t) = parser(i).expression(i).unwrap();
+            t
+        };
+        assert!(matches!(check(":a"), N3Term::Iri(_)));
+        assert!(matches!(check("\"a\"@en"), N3Term::Literal(_)));
+        assert!(matches!(check("_:a"), N3Term::Existential(_)));
+        assert!(matches!(check("[]"), N3Term::Existential(_)));
+        assert!(matches!(check("[ :p :o ]"), N3Term::Existential(_)));
+        assert!(matches!(check("?a"), N3Term::Universal(_)));
+        assert!(matches!(check("{ }"), N3Term::Formula(_)));
+        assert!(matches!(check("( )"), N3Term::Iri(_)));
+    }
 }
@@ -17,6 +17,10 @@ lazy_static! {
     ///
     /// `variable ::= '?' VARNAME`
     pub static ref VARIABLE: Regex = Regex::new(r#"^\?[_A-Za-z0-9\u{00C0}-\u{00D6}\u{00D8}-\u{00F6}\u{00F8}-\u{02FF}\u{0370}-\u{037D}\u{037F}-\u{1FFF}\u{200C}-\u{200D}\u{2070}-\u{218F}\u{2C00}-\u{2FEF}\u{3001}-\u{D7FF}\u{F900}-\u{FDCF}\u{FDF0}-\u{FFFD}\U{00010000}-\U{000EFFFF}][_A-Za-z0-9\u{00B7}\u{00C0}-\u{00D6}\u{00D8}-\u{00F6}\u{00F8}-\u{02FF}\u{0300}-\u{037D}\u{037F}-\u{1FFF}\u{200C}-\u{200D}\u{203F}-\u{2040}\u{2070}-\u{218F}\u{2C00}-\u{2FEF}\u{3001}-\u{D7FF}\u{F900}-\u{FDCF}\u{FDF0}-\u{FFFD}\U{00010000}-\U{000EFFFF}]*"#).unwrap();
+    /// Production of N3's bare names, i.e. names without a prefix.
+    ///
+    /// `barename ::= PN_CHARS_U PN_CHARS*`
+    pub static ref BARENAME: Regex = Regex::new(r#"^[_A-Za-z\u{00C0}-\u{00D6}\u{00D8}-\u{00F6}\u{00F8}-\u{02FF}\u{0370}-\u{037D}\u{037F}-\u{1FFF}\u{200C}-\u{200D}\u{2070}-\u{218F}\u{2C00}-\u{2FEF}\u{3001}-\u{D7FF}\u{F900}-\u{FDCF}\u{FDF0}-\u{FFFD}\U{00010000}-\U{000EFFFF}][-0-9_A-Za-z\u{00B7}\u{00C0}-\u{00D6}\u{00D8}-\u{00F6}\u{00F8}-\u{02FF}\u{0300}-\u{037D}\u{037F}-\u{1FFF}\u{200C}-\u{200D}\u{203F}-\u{2040}\u{2070}-\u{218F}\u{2C00}-\u{2FEF}\u{3001}-\u{D7FF}\u{F900}-\u{FDCF}\u{FDF0}-\u{FFFD}\U{00010000}-\U{000EFFFF}]*"#).unwrap();
 }
 
 #[cfg(test)]
@@ -34,4 +38,13 @@ mod test {
     fn check_variable(to_check: &str) -> bool {
         VARIABLE.is_match(to_check)
     }
+
+    #[test_case("" => None ; "empty")]
+    #[test_case("hans" => Some("hans") ; "alpha")]
+    #[test_case("_x-1 rest" => Some("_x-1") ; "with rest")]
+    #[test_case("x.y" => Some("x") ; "no dots")]
+    #[test_case("1x" => None ; "leading number")]
+    fn check_barename(to_check: &str) -> Option<&str> {
+        BARENAME.find(to_check).map(|m| m.as_str())
+    }
 }
@@ -5,37 +5,190 @@
 //! For now this is a straight forward implementation. No efforts were taken
 //! regarding performance.
 //!
+//! `Parser` requires the whole document in memory. To parse large documents
+//! use the `StreamParser` which reads the document chunk by chunk from a
+//! `BufRead`.
+//!
 //! # Completeness
 //!
-//! While aiming at feature-completeness later, for now some basic Turtle
-//! features are (knowingly) missing:
+//! `@base` and `BASE` may appear between any statements. Relative IRIs are
+//! resolved against the base IRI in effect at their position. Known
+//! limitations are:
 //!
-//! - Functionality of base IRI.
-//! - Correct resolving of relative IRIs.
-//! - Escape characters (ECHAR and UCHAR).
+//! - Without a base IRI, neither from the document nor from
+//!   `Parser::with_base()`, relative IRIs are returned unresolved instead of
+//!   raising an error.
 //!
-//! In the future it is planned to execute the parser against the W3C test
-//! suite where probably further missing features will pop up.
+//! The parser is tested against the W3C test suite, see the `test_suite`
+//! module for how to run it in full.
 //!
 
 pub mod production;
+pub mod stream;
 pub mod terminals;
 
 #[cfg(test)]
 mod test_suite;
 
-use self::production::statement;
+pub use self::stream::StreamParser;
+
 use self::terminals::multispace0;
 use crate::error::{Error, Result};
-use crate::parse::Context;
+use crate::parse::{locate_err, Context};
 use crate::Turtle;
-use sophia::term::{iri::Iri, mown_str::MownStr, Term};
+use sophia::parser::TripleParser;
+use sophia::term::{iri::Iri, mown_str::MownStr, BoxTerm, Term};
+use std::io::BufRead;
 
 /// Shortcut for `Term<MownStr<'doc>>`.
 pub type MownTerm<'doc> = Term<MownStr<'doc>>;
 
+/// `sophia`'s interface to the Turtle parser.
+///
+/// The parser is configured by the fields of this struct and can be used as
+/// any other `TripleParser` of `sophia`:
+///
+/// ```
+/// use metis::parse::turtle::TurtleParser;
+/// use sophia::term::BoxTerm;
+/// use sophia::triple::stream::TripleSource;
+///
+/// let doc = "<s> <p> <o> .";
+/// let parser = TurtleParser {
+///     base: Some("http://example.org/".to_string()),
+///     ..TurtleParser::default()
+/// };
+/// let mut g: Vec<[BoxTerm; 3]> = vec![];
+/// parser.parse_str(doc).in_graph(&mut g).unwrap();
+/// assert_eq!(g.len(), 1);
+/// ```
+///
+/// Errors are returned as `crate::error::Error` which is `'static`, `Send`
+/// and `Sync` so they can be wrapped by `sophia`'s `StreamError`. Errors of
+/// the parser itself are `Error::Parser` and contain the position where
+/// parsing failed.
+#[derive(Clone, Debug, Default)]
+pub struct TurtleParser {
+    /// The base IRI to resolve relative IRIs of the document.
+    ///
+    /// _Note:_ If the document contains an own `base` directive the pre-set
+    /// value is overridden. An invalid base IRI is returned as the first
+    /// error of the parsed triples.
+    pub base: Option<String>,
+    /// Continue parsing after an invalid statement.
+    ///
+    /// See `Parser::set_lenient()`.
+    pub lenient: bool,
+}
+
+impl TurtleParser {
+    /// Parses a document borrowing the terms from it if possible.
+    pub fn parse_str<'doc>(&self, doc: &'doc str) -> Parser<'doc> {
+        let mut parser = match &self.base {
+            None => Parser::new(doc),
+            Some(base) => Iri::new(MownStr::from(base.clone()))
+                .map_err(Error::from)
+                .and_then(|base| Parser::with_base(doc, base))
+                .unwrap_or_else(|err| Parser::failed(doc, err)),
+        };
+        parser.set_lenient(self.lenient);
+        parser
+    }
+    /// Parses a document from a `BufRead`.
+    ///
+    /// The document is read chunk by chunk. Therefore, the triples own their
+    /// terms as they can not borrow from the input.
+    pub fn parse_bufread<B: BufRead>(&self, bufread: B) -> StreamParser<B> {
+        let mut parser = StreamParser::new(bufread);
+        if let Some(base) = &self.base {
+            parser = parser.with_base_str(base);
+        }
+        parser.set_lenient(self.lenient);
+        parser
+    }
+}
+
+/// Copies the terms of a triple into owned ones.
+fn to_box(t: [MownTerm<'_>; 3]) -> [BoxTerm; 3] {
+    [t[0].clone_into(), t[1].clone_into(), t[2].clone_into()]
+}
+
+/// Skips the statement at the start of `i`.
+///
+/// Returns the input after the next `.` that terminates a statement, i.e. a
+/// `.` that is not within an IRI, a literal, a comment or brackets and that is
+/// not part of a name or number. If the brackets are not balanced the first
+/// `.` that is not within an IRI, a literal or a comment is used instead.
+///
+/// If `complete` is false `i` may be continued. Therefore, a `.` at the end of
+/// `i` is not considered.
+fn skip_statement(i: &str, complete: bool) -> Option<&str> {
+    let bytes = i.as_bytes();
+    let mut depth = 0_usize;
+    let mut fallback = None;
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'#' => {
+                while idx < bytes.len() && bytes[idx] != b'\n' {
+                    idx += 1;
+                }
+            }
+            b'<' => {
+                idx += 1;
+                while idx < bytes.len() && !b"> \t\r\n".contains(&bytes[idx]) {
+                    idx += 1;
+                }
+            }
+            quote @ b'"' | quote @ b'\'' => {
+                let long = bytes[idx..].starts_with(&[quote; 3]);
+                idx += if long { 3 } else { 1 };
+                while idx < bytes.len() {
+                    match bytes[idx] {
+                        b'\\' => idx += 1,
+                        b'\n' if !long => break,
+                        c if c == quote && !long => break,
+                        c if c == quote && bytes[idx..].starts_with(&[quote; 3]) => {
+                            idx += 2;
+                            break;
+                        }
+                        _ => {}
+                    }
+                    idx += 1;
+                }
+            }
+            b'[' | b'(' | b'{' => depth += 1,
+            b']' | b')' | b'}' => depth = depth.saturating_sub(1),
+            b'.' => match bytes.get(idx + 1) {
+                None if !complete => break,
+                // part of a name or number
+                Some(c) if c.is_ascii_alphanumeric() || *c >= 0x80 || b"_-:%\\".contains(c) => {}
+                _ if depth == 0 => return Some(&i[idx + 1..]),
+                _ => {
+                    fallback.get_or_insert(idx + 1);
+                }
+            },
+            _ => {}
+        }
+        idx += 1;
+    }
+
+    fallback.map(|idx| &i[idx..])
+}
+
+impl<B: BufRead> TripleParser<B> for TurtleParser {
+    type Source = StreamParser<B>;
+
+    fn parse(&self, data: B) -> Self::Source {
+        self.parse_bufread(data)
+    }
+}
+
 /// The Turtle parser that parses a document step by step.
 pub struct Parser<'doc> {
+    /// The whole document. Used to locate errors.
+    doc: &'doc str,
     /// Gathered metadata.
     ctx: Context<'doc, Turtle>,
     /// Current position within the document.
@@ -44,17 +197,26 @@ pub struct Parser<'doc> {
     ///
     /// In both cases the `next() = None`.
     end_or_failed: bool,
+    /// An error that occurred before parsing, e.g. an invalid base IRI.
+    ///
+    /// It is returned by the first call of `next()`.
+    error: Option<Error>,
+    /// Continue parsing after an invalid statement.
+    lenient: bool,
 }
 
 impl<'doc> Parser<'doc> {
     /// Creates a new Parser.
     pub fn new(doc: &'doc str) -> Self {
         // trim leading whitespaces
-        let (doc, _) = multispace0(doc).unwrap();
+        let (current, _) = multispace0(doc).unwrap();
         Self {
+            doc,
             ctx: Context::default(),
-            current: doc,
+            current,
             end_or_failed: false,
+            error: None,
+            lenient: false,
         }
     }
     /// A new parser with a pre-set base IRI to resolve `iri` productions.
@@ -63,16 +225,46 @@ impl<'doc> Parser<'doc> {
     /// value is overridden.
     pub fn with_base(doc: &'doc str, base: Iri<MownStr<'doc>>) -> Result<Self> {
         let mut ctx = Context::default();
-        ctx.prolog.set_base(base)?;
+        ctx.prolog.to_mut().set_base(base)?;
 
         // trim leading whitespaces
-        let (doc, _) = multispace0(doc).unwrap();
+        let (current, _) = multispace0(doc).unwrap();
         Ok(Self {
+            doc,
             ctx,
-            current: doc,
+            current,
             end_or_failed: false,
+            error: None,
+            lenient: false,
         })
     }
+    /// Sets if the parser continues after an invalid statement.
+    ///
+    /// By default, the parser stops at the first error. In lenient mode the
+    /// parser reports the error, skips forward to the next `.` that terminates
+    /// a statement and continues parsing. The triples of the invalid
+    /// statement are dropped.
+    ///
+    /// _Note:_ Errors that are not caused by the document, e.g. an invalid
+    /// pre-set base IRI, still stop the parser.
+    pub fn set_lenient(&mut self, lenient: bool) -> &mut Self {
+        self.lenient = lenient;
+        self
+    }
+    /// A parser that continues with the given context.
+    fn with_context(doc: &'doc str, ctx: Context<'doc, Turtle>) -> Self {
+        Self {
+            ctx,
+            ..Self::new(doc)
+        }
+    }
+    /// A parser that returns only `err`.
+    fn failed(doc: &'doc str, err: Error) -> Self {
+        Self {
+            error: Some(err),
+            ..Self::new(doc)
+        }
+    }
 }
 
 impl<'doc> Iterator for Parser<'doc> {
@@ -84,7 +276,10 @@ impl<'doc> Iterator for Parser<'doc> {
     /// a statement are stored internally. When all triples of a parsed
     /// statement are returned the next statement is parsed.
     fn next(&mut self) -> Option<Self::Item> {
-        if self.end_or_failed {
+        if let Some(err) = self.error.take() {
+            self.end_or_failed = true;
+            return Some(Err(err));
+        } else if self.end_or_failed {
             // parser finished
             return None;
         } else if let Some(tri) = self.ctx.pop_triple() {
@@ -97,12 +292,21 @@ impl<'doc> Iterator for Parser<'doc> {
         }
 
         // parse new triples
-        let step = statement(&self.current, &self.ctx);
+        let step = self.ttl_statement(self.current);
         let rest = match step {
             Ok((rest, _)) => rest,
             Err(e) => {
-                self.end_or_failed = true;
-                return Some(Err(Error::Parser(e.to_string())));
+                let err = locate_err(self.doc, e);
+                if self.lenient {
+                    self.ctx.triple_stack.clear();
+                    let rest = skip_statement(self.current, true).unwrap_or("");
+                    // multispace0 never fails
+                    let (rest, _) = multispace0(rest).unwrap();
+                    self.current = rest;
+                } else {
+                    self.end_or_failed = true;
+                }
+                return Some(Err(err));
             }
         };
         // multispace0 never fails
@@ -116,8 +320,11 @@ impl<'doc> Iterator for Parser<'doc> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::parse::Error as ParserErrorKind;
+    use sophia::ns::rdf;
     use sophia::serializer::{nt, Stringifier, TripleSerializer};
-    use sophia::triple::stream::TripleSource;
+    use sophia::triple::stream::{SourceError, StreamError, TripleSource};
+    use test_case::test_case;
 
     #[test]
     fn parse_example() -> std::result::Result<(), Box<dyn std::error::Error>> {
@@ -181,6 +388,241 @@ mod test {
             .serialize_graph(&g)?
             .to_string();
         println!("Serialized: \n\n {}", s);
+        assert_eq!(g.len(), 29);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_nested() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let example = r#"
+        @prefix : <http://example.org/> .
+        PREFIX ex: <http://example.org/ns#>
+
+        :s :p [ :q [ :r :o ] ; ] ;
+            ex:list ( 1 2.5 -3.0e1 [ :p true ] ) ;
+            ex:empty () .
+        [ :p :o ] .
+        ( :a :b ) a ex:List.
+        "#;
+
+        let triples = Parser::new(example).collect::<Result<Vec<_>>>()?;
+        // nested blank nodes
+        assert_eq!(
+            triples
+                .iter()
+                .filter(|[s, p, o]| &*s.value() == "http://example.org/s"
+                    && &*p.value() == "http://example.org/p"
+                    && matches!(o, Term::BNode(_)))
+                .count(),
+            1
+        );
+        // collections
+        assert_eq!(
+            triples
+                .iter()
+                .filter(|[_, p, _]| *p.value() == *rdf::iri::first.value())
+                .count(),
+            6
+        );
+        assert_eq!(
+            triples
+                .iter()
+                .filter(|[_, _, o]| *o.value() == *rdf::iri::nil.value())
+                .count(),
+            3
+        );
+        assert_eq!(triples.len(), 20);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_anon_next_to_label() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let example = r#"
+        [] <http://example.org/p> <http://example.org/o> .
+        _:anon0 <http://example.org/p> <http://example.org/o> .
+        _:anon0 <http://example.org/q> _:anon_anon0 .
+        "#;
+
+        let triples = Parser::new(example).collect::<Result<Vec<_>>>()?;
+        assert_ne!(triples[0][0], triples[1][0]);
+        assert_eq!(triples[1][0], triples[2][0]);
+        assert_ne!(triples[2][0], triples[2][2]);
+        assert_ne!(triples[0][0], triples[2][2]);
         Ok(())
     }
+
+    #[test]
+    fn parse_directives_anywhere() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let example = r#"
+        @base <http://example.org/a/> .
+        @prefix : <ns#> .
+        <s> :p <o> .
+        BASE <b/>
+        PREFIX : <ns2#>
+        <s> :p <o> .
+        @base <../c/> .
+        <s> :p <o> .
+        "#;
+
+        let triples = Parser::new(example).collect::<Result<Vec<_>>>()?;
+        let values: Vec<_> = triples
+            .iter()
+            .map(|[s, p, _]| (s.value().to_string(), p.value().to_string()))
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                (
+                    "http://example.org/a/s".to_string(),
+                    "http://example.org/a/ns#p".to_string()
+                ),
+                (
+                    "http://example.org/a/b/s".to_string(),
+                    "http://example.org/a/b/ns2#p".to_string()
+                ),
+                (
+                    "http://example.org/a/c/s".to_string(),
+                    "http://example.org/a/b/ns2#p".to_string()
+                ),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn error_position() {
+        let example = "@prefix : <http://ex/> .\n:s :p :o .\n:s :p unknown:o .\n";
+        let err = Parser::new(example)
+            .find_map(Result::err)
+            .expect("Undefined prefix");
+        match err {
+            Error::Parser {
+                offset,
+                line,
+                column,
+                kind: ParserErrorKind::InvalidPrefix(prefix),
+            } => {
+                assert_eq!(offset, example.find("unknown").unwrap());
+                assert_eq!(line, 3);
+                assert_eq!(column, 7);
+                assert_eq!(prefix, "unknown");
+            }
+            e => panic!("Unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn parse_missing_dot() {
+        let example = "<http://example.org/s> <http://example.org/p> <http://example.org/o>";
+        let mut parser = Parser::new(example);
+        let err = match parser.next() {
+            Some(Err(err)) => err,
+            res => panic!("Expected an error but got {:?}", res),
+        };
+        assert!(parser.next().is_none());
+
+        let diagnostic = err.diagnostic(example).expect("Parser error").to_string();
+        assert!(diagnostic.starts_with("error: Expected `.` after triples\n --> 1:69\n"));
+    }
+
+    #[test]
+    fn turtle_parser_str() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let example = "@prefix : <ns#> .\n<s> :p :o, <o2> .\n";
+        let parser = TurtleParser {
+            base: Some("http://example.org/".to_string()),
+            ..TurtleParser::default()
+        };
+        let mut g: Vec<[BoxTerm; 3]> = vec![];
+        parser.parse_str(example).in_graph(&mut g)?;
+
+        assert_eq!(g.len(), 2);
+        assert_eq!(&*g[0][0].value(), "http://example.org/s");
+        assert_eq!(&*g[0][1].value(), "http://example.org/ns#p");
+        assert_eq!(&*g[1][2].value(), "http://example.org/o2");
+        Ok(())
+    }
+
+    #[test]
+    fn turtle_parser_bufread() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let example = "<http://ex/s> <http://ex/p> ( 1 2 ) .\n";
+        let mut g: Vec<[BoxTerm; 3]> = vec![];
+        TurtleParser::default()
+            .parse(example.as_bytes())
+            .in_graph(&mut g)?;
+        assert_eq!(g.len(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn turtle_parser_source_error() {
+        let mut g: Vec<[BoxTerm; 3]> = vec![];
+        let res = TurtleParser::default()
+            .parse_str("<http://ex/s> <http://ex/p> .")
+            .in_graph(&mut g);
+        match res {
+            Err(StreamError::SourceError(Error::Parser { line, column, .. })) => {
+                assert_eq!((line, column), (1, 29));
+            }
+            res => panic!("Unexpected result: {:?}", res.map(|_| ())),
+        }
+        assert!(g.is_empty());
+
+        let mut parser = TurtleParser {
+            base: Some("not an absolute IRI".to_string()),
+            ..TurtleParser::default()
+        }
+        .parse_str("<s> <p> <o> .");
+        assert!(matches!(parser.next(), Some(Err(_))));
+        assert!(parser.next().is_none());
+    }
+
+    #[test_case("<s> <p> <o> . <s2>" => Some(" <s2>") ; "simple")]
+    #[test_case("<s> <p> :a.b, 1.5 . <s2>" => Some(" <s2>") ; "names and numbers")]
+    #[test_case("<s.> <p> \"a . b\", 'c . d' # e . f\n. <s2>" => Some(" <s2>") ; "quoted")]
+    #[test_case("<s> <p> \"\"\"a \" . \"\" . \"\"\" . <s2>" => Some(" <s2>") ; "long string")]
+    #[test_case("<s> <p> \"a \\\" . b\" . <s2>" => Some(" <s2>") ; "escaped quote")]
+    #[test_case("<s> <p> [ <q> <o> . <s2> ] . <s3>" => Some(" <s3>") ; "nested")]
+    #[test_case("<s> <p> [ <q> <o> . <s2>" => Some(" <s2>") ; "unbalanced")]
+    #[test_case("<s> <p> <o>" => None ; "missing dot")]
+    fn check_skip_statement(i: &str) -> Option<&str> {
+        skip_statement(i, true)
+    }
+
+    #[test]
+    fn check_skip_statement_incomplete() {
+        assert_eq!(skip_statement("<s> <p> :o.", false), None);
+        assert_eq!(skip_statement("<s> <p> :o.", true), Some(""));
+    }
+
+    #[test]
+    fn parse_lenient() {
+        let example = r#"@prefix : <http://example.org/> .
+        :s :p :o1 .
+        :s :p unknown:o .
+        :s :p [ :q :o ] :o .
+        :s :p :o2 .
+        :s :p :o3"#;
+
+        let mut parser = Parser::new(example);
+        parser.set_lenient(true);
+        let results: Vec<_> = parser.collect();
+        let lines: Vec<_> = results
+            .iter()
+            .map(|res| match res {
+                Ok([_, _, o]) => o.value().to_string(),
+                Err(Error::Parser { line, .. }) => format!("error in line {}", line),
+                Err(e) => panic!("Unexpected error: {}", e),
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "http://example.org/o1",
+                "error in line 3",
+                "error in line 4",
+                "http://example.org/o2",
+                "error in line 6",
+            ]
+        );
+    }
 }
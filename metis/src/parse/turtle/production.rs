@@ -8,227 +8,98 @@
 //!   the target object from the `&str`.
 //!   If building fails `nom::Err::Failure` is returned.
 
-use super::{terminals::*, Context, MownTerm, Parser};
-use crate::collections::*;
+use super::{terminals::*, MownTerm, Parser};
 use crate::parse::{
-    parse_regex, unwrap_str, Error, IntoPR as _, MapPR as _, OrIntoPR as _, PResult, PosError,
+    parse_regex, unwrap_str, CutPR as _, Error, IntoPR as _, MapPR as _, OrIntoPR as _, PResult,
+    PosError,
 };
-use crate::Turtle;
 use nom::branch::alt;
 use nom::bytes::complete::{tag, tag_no_case};
-use nom::combinator::{map, map_opt, opt};
-use nom::multi::{many0, separated_list};
-use nom::sequence::{preceded, tuple};
-use nom::{error::ErrorKind, error_position, Err as NomError, IResult};
+use nom::combinator::{map, map_opt};
+use nom::sequence::tuple;
+use nom::{Err as NomError, IResult};
 use sophia::ns::{rdf, xsd};
-use sophia::term::{blank_node::BlankNode, iri::Iri, literal::Literal, mown_str::MownStr, Term};
-use std::cell::RefCell;
+use sophia::term::{blank_node::BlankNode, iri::Iri, literal::Literal, mown_str::MownStr};
 
-/// A context wrapped in a RefCell.
+/// Apply the escape sequences of ECHAR and UCHAR.
 ///
-/// This is necessary due to the constraints of `nom`'s parser generators (they
-/// only take `Fn`).
-pub type RefContext<'a> = RefCell<Context<'a, Turtle>>;
-
-/// Apply the escape sequences of ECHAR
+/// Both kinds of escape sequences are resolved in one pass, otherwise an
+/// escaped backslash followed by `u` would be resolved twice. If the input
+/// contains no escape sequence it is borrowed.
 ///
-/// TODO: implement
-pub(crate) fn string_escape<'a>(i: impl Into<MownStr<'a>>) -> MownStr<'a> {
-    i.into()
+/// [159s] ECHAR ::= '\' [tbnrf"'\]
+pub(crate) fn string_escape(i: &str) -> Result<MownStr<'_>, Error> {
+    unescape(i, true)
 }
 
-/// Apply the escape sequences of UCHAR
+/// Apply the escape sequences of UCHAR.
+///
+/// If the input contains no escape sequence it is borrowed.
 ///
-/// TODO: implement
-pub(crate) fn numeric_escape<'a>(i: impl Into<MownStr<'a>>) -> MownStr<'a> {
-    i.into()
+/// [26] UCHAR ::= '\u' HEX HEX HEX HEX | '\U' HEX HEX HEX HEX HEX HEX HEX HEX
+pub(crate) fn numeric_escape(i: &str) -> Result<MownStr<'_>, Error> {
+    unescape(i, false)
 }
 
-/// Parses Turtle's production
-/// [1] turtleDoc ::= statement*
-// pub fn doc<'a>(i: &'a str, ctx: &RefContext<'a>) -> IResult<&'a str, ()> {
-//     many0(tuple((|i| statement(i, ctx), multispace0)))(i).map(|(rest, _)| (rest, ()))
-// }
-
-/// Parses Turtle's production
-/// [2] statement ::= directive | triples '.'
-// pub fn statement<'a>(i: &'a str, ctx: &RefContext<'a>) -> IResult<&'a str, ()> {
-//     let (rest, parsed) = alt((
-//         map(|i| directive(i, ctx), |_| None),
-//         map(
-//             tuple((|i| triples(i, ctx), multispace0, tag("."))),
-//             |(spo, _, _)| Some(spo),
-//         ),
-//     ))(i)?;
-
-//     if let Some(spo) = parsed {
-//         ctx.borrow_mut().push_triples(spo.into_iter());
-//     }
-//     Ok((rest, ()))
-// }
-
-/// Parses Turtle's production
-/// [3] directive ::= prefixID | base | sparqlPrefix | sparqlBase
-// pub fn directive<'a>(i: &'a str, ctx: &RefContext<'a>) -> IResult<&'a str, ()> {
-//     alt((|i| prefix_id(i, ctx), |i| base(i, ctx)))(i)
-// }
-
-/// Parses Turtle's production
-/// [6] triples ::= subject predicateObjectList | blankNodePropertyList predicateObjectList?
-// pub fn triples<'a>(i: &'a str, ctx: &RefContext<'a>) -> IResult<&'a str, SpoList<'a, Turtle>> {
-//     alt((
-//         map(
-//             tuple((
-//                 |i| subject(i, ctx),
-//                 multispace1,
-//                 |i| predicate_object_list(i, ctx),
-//             )),
-//             |(s, _, po)| SpoList::new(s, po),
-//         ),
-//         map(
-//             tuple((
-//                 |i| blank_node_property_list(i, ctx),
-//                 opt(|i| predicate_object_list(i, ctx)),
-//             )),
-//             |(bn_po, outer_po)| {
-//                 let mut bn_po = bn_po;
-//                 if let Some(outer) = outer_po {
-//                     bn_po.extend(outer.into_iter());
-//                 }
-
-//                 let bn = ctx.borrow_mut().new_anon_bnode();
-//                 SpoList::new(bn.clone(), bn_po)
-//             },
-//         ),
-//     ))(i)
-// }
-
-/// Parses Turtle's production
-/// [7] predicateObjectList ::= verb objectList (';' (verb objectList)?)*
-// pub fn predicate_object_list<'a>(
-//     i: &'a str,
-//     ctx: &RefContext<'a>,
-// ) -> IResult<&'a str, Vec<PoList<'a, Turtle>>> {
-//     separated_list(
-//         tuple((multispace0, tag(";"), multispace0)),
-//         map(
-//             tuple((|i| verb(i, ctx), multispace1, |i| object_list(i, ctx))),
-//             |(verb, _, objects)| PoList::new(verb, objects),
-//         ),
-//     )(i)
-// }
-
-/// Parses Turtle's production
-/// [8] objectList ::= object (',' object)*
-// pub fn object_list<'a>(i: &'a str, ctx: &RefContext<'a>) -> IResult<&'a str, TermList<'a, Turtle>> {
-//     let (rest, list) = separated_list(tuple((multispace0, tag(","), multispace0)), |i| {
-//         object(i, ctx)
-//     })(i)?;
-
-//     if list.len() == 0 {
-//         Err(NomError::Error(error_position!(rest, ErrorKind::Verify)))
-//     } else {
-//         Ok((rest, list))
-//     }
-// }
-
-/// Parses Turtle's production
-/// [9] verb ::= predicate | 'a'
-// pub fn verb<'a>(i: &'a str, ctx: &RefContext<'a>) -> IResult<&'a str, MownTerm<'a>> {
-//     alt((
-//         |i| predicate(i, ctx),
-//         map(tag("a"), |_| Term::from(&rdf::type_)),
-//     ))(i)
-// }
-
-/// Parses Turtle's production
-/// [10] subject ::= iri | BlankNode | collection
-// pub fn subject<'a>(i: &'a str, ctx: &RefContext<'a>) -> IResult<&'a str, MownTerm<'a>> {
-//     alt((
-//         |i| iri(i, ctx),
-//         |i| blank_node(i, ctx),
-//         |i| collection(i, ctx),
-//     ))(i)
-// }
-
-/// Parses Turtle's production
-/// [11] predicate ::= iri
-// pub fn predicate<'a>(i: &'a str, ctx: &RefContext<'a>) -> IResult<&'a str, MownTerm<'a>> {
-//     iri(i, ctx)
-// }
+/// Apply the escape sequences of PN_LOCAL_ESC, i.e. drop the `\`.
+///
+/// If the input contains no escape sequence it is borrowed.
+pub(crate) fn local_escape(i: &str) -> MownStr<'_> {
+    if i.contains('\\') {
+        i.replace('\\', "").into()
+    } else {
+        i.into()
+    }
+}
 
-/// Parses Turtle's production
-/// [12] object ::= iri | BlankNode | collection | blankNodePropertyList | literal
-// pub fn object<'a>(i: &'a str, ctx: &RefContext<'a>) -> IResult<&'a str, MownTerm<'a>> {
-//     alt((
-//         |i| iri(i, ctx),
-//         |i| blank_node(i, ctx),
-//         |i| collection(i, ctx),
-//         map(
-//             |i| blank_node_property_list(i, ctx),
-//             |pol| {
-//                 let bn = ctx.borrow_mut().new_anon_bnode();
-//                 let spos = SpoList::new(bn.clone(), pol);
-//                 ctx.borrow_mut().push_triples(spos.into_iter());
-//                 bn
-//             },
-//         ),
-//         |i| literal(i, ctx),
-//     ))(i)
-// }
+/// Resolves UCHAR and, if `echar` is set, ECHAR escape sequences.
+fn unescape(i: &str, echar: bool) -> Result<MownStr<'_>, Error> {
+    let first = match i.find('\\') {
+        Some(idx) => idx,
+        None => return Ok(i.into()),
+    };
+    let mut buf = String::with_capacity(i.len());
+    buf.push_str(&i[..first]);
+    let mut rest = &i[first..];
+
+    while let Some(idx) = rest.find('\\') {
+        buf.push_str(&rest[..idx]);
+        rest = &rest[idx..];
+
+        let (len, c) = match rest.as_bytes().get(1) {
+            Some(b'u') => (6, code_point(rest.get(2..6))?),
+            Some(b'U') => (10, code_point(rest.get(2..10))?),
+            Some(b't') if echar => (2, '\t'),
+            Some(b'b') if echar => (2, '\u{8}'),
+            Some(b'n') if echar => (2, '\n'),
+            Some(b'r') if echar => (2, '\r'),
+            Some(b'f') if echar => (2, '\u{c}'),
+            Some(b'"') if echar => (2, '"'),
+            Some(b'\'') if echar => (2, '\''),
+            Some(b'\\') if echar => (2, '\\'),
+            _ => {
+                let end = rest.char_indices().nth(2).map_or(rest.len(), |(idx, _)| idx);
+                return Err(Error::InvalidEscape(rest[..end].to_string()));
+            }
+        };
+        buf.push(c);
+        rest = &rest[len..];
+    }
+    buf.push_str(rest);
 
-/// Parses Turtle's production
-/// [14] blankNodePropertyList ::= '[' predicateObjectList ']'
-// pub fn blank_node_property_list<'a>(
-//     i: &'a str,
-//     ctx: &RefContext<'a>,
-// ) -> IResult<&'a str, Vec<PoList<'a, Turtle>>> {
-//     let (rest, _) = tag("[")(i)?;
-//     let (rest, _) = multispace0(rest)?;
-//     let (rest, contents) = predicate_object_list(rest, ctx)?;
-//     let (rest, _) = multispace0(rest)?;
-//     let (rest, _) = tag("]")(rest)?;
-
-//     Ok((rest, contents))
-// }
+    Ok(buf.into())
+}
 
-/// Parses Turtle's production
-/// [15] collection ::= '(' object* ')'
+/// Converts the hex digits of an UCHAR into a `char`.
 ///
-/// # Result
-///
-/// Returns the subject of the first element in the collection. If the
-/// collection is empty `rdf:nil` is returned.
-// pub fn collection<'a>(i: &'a str, ctx: &RefContext<'a>) -> IResult<&'a str, MownTerm<'a>> {
-//     let (rest, _) = tag("(")(i)?;
-//     let (rest, _) = multispace0(rest)?;
-//     let (rest, contents) = separated_list(multispace1, |i| object(i, ctx))(rest)?;
-//     let (rest, _) = multispace0(rest)?;
-//     let (rest, _) = tag(")")(rest)?;
-
-//     if contents.is_empty() {
-//         Ok((rest, Term::from(&rdf::nil)))
-//     } else {
-//         let mut cur;
-//         let mut next = ctx.borrow_mut().new_anon_bnode();
-//         let first = next.clone();
-//         let max = contents.len() - 1;
-//         for (idx, o) in contents.into_iter().enumerate() {
-//             cur = next;
-//             next = if idx != max {
-//                 ctx.borrow_mut().new_anon_bnode()
-//             } else {
-//                 Term::from(&rdf::nil)
-//             };
-//             ctx.borrow_mut()
-//                 .push_triple([cur.clone(), Term::from(&rdf::first), o]);
-//             ctx.borrow_mut()
-//                 .push_triple([cur, Term::from(&rdf::rest), next.clone()]);
-//         }
-
-//         Ok((rest, first))
-//     }
-// }
+/// Code points of surrogates or beyond `U+10FFFF` are rejected.
+fn code_point(hex: Option<&str>) -> Result<char, Error> {
+    let hex = hex.ok_or_else(|| Error::InvalidEscape("incomplete UCHAR".to_string()))?;
+    u32::from_str_radix(hex, 16)
+        .ok()
+        .and_then(std::char::from_u32)
+        .ok_or_else(|| Error::InvalidEscape(format!("code point {}", hex)))
+}
 
 // SPARQL rules.
 impl<'doc> Parser<'doc> {
@@ -238,9 +109,12 @@ impl<'doc> Parser<'doc> {
     /// [5s] sparqlBase ::= "BASE" IRIREF
     pub fn sparql_base(&mut self, i: &'doc str) -> PResult<'doc, ()> {
         let (rest, base) = sparql_base(i).map_pr()?;
+        let (_, base) = numeric_escape(base).into_pr(i, rest)?;
+        let (_, base) = Iri::new(base).into_pr(i, rest)?;
         self.ctx
             .prolog
-            .set_base(Iri::new(base).expect("Just check if absolute"))
+            .to_mut()
+            .set_base(base)
             .map(|_| ())
             .into_pr(i, rest)
     }
@@ -251,7 +125,8 @@ impl<'doc> Parser<'doc> {
     /// [6s] sparqlPrefix ::= "PREFIX" PNAME_NS IRIREF
     pub fn sparql_prefix(&mut self, i: &'doc str) -> PResult<'doc, ()> {
         let (rest, (prefix, ns)) = sparql_prefix(i).map_pr()?;
-        self.ctx.prolog.add_prefix(prefix, ns)
+        let (_, ns) = numeric_escape(ns).into_pr(i, rest)?;
+        self.ctx.prolog.to_mut().add_prefix(prefix, ns)
             .map(|_| ())
             .map_err(|_| Error::InvalidPrefix(prefix.to_string()))
             .into_pr(i, rest)
@@ -262,68 +137,205 @@ impl<'doc> Parser<'doc> {
 impl<'doc> Parser<'doc> {
     /// Parses Turtle's production
     /// [1] turtleDoc ::= statement*
-    pub fn ttl_doc(&mut self, i: &'doc str) -> PResult<'doc, MownTerm<'doc>> {
-        unimplemented!()
+    ///
+    /// All parsed triples are pushed onto the context's stack.
+    pub fn ttl_doc(&mut self, i: &'doc str) -> PResult<'doc, ()> {
+        let (mut rest, _) = multispace0(i).map_pr()?;
+        while !rest.is_empty() {
+            let (r, _) = self.ttl_statement(rest)?;
+            let (r, _) = multispace0(r).map_pr()?;
+            rest = r;
+        }
+        Ok((rest, ()))
     }
-    
+
     /// Parses Turtle's production
     /// [2] statement ::= directive | triples '.'
-    pub fn ttl_statement(&mut self, i: &'doc str) -> PResult<'doc, MownTerm<'doc>> {
-        unimplemented!()
+    ///
+    /// All parsed triples are pushed onto the context's stack.
+    pub fn ttl_statement(&mut self, i: &'doc str) -> PResult<'doc, ()> {
+        match self.ttl_directive(i) {
+            Err(NomError::Error(_)) => {}
+            res => return res,
+        };
+
+        let (rest, _) = self.ttl_triples(i)?;
+        let (rest, _) = multispace0(rest).map_pr()?;
+        let (rest, _) = tag(".")(rest).map_pr().cut_expecting("`.` after triples")?;
+        Ok((rest, ()))
     }
-    
+
     /// Parses Turtle's production
     /// [3] directive ::= prefixID | base | sparqlPrefix | sparqlBase
-    pub fn ttl_directive(&mut self, i: &'doc str) -> PResult<'doc, MownTerm<'doc>> {
-        unimplemented!()
+    pub fn ttl_directive(&mut self, i: &'doc str) -> PResult<'doc, ()> {
+        self.alt(
+            i,
+            &[
+                &Self::prefix,
+                &Self::base,
+                &Self::sparql_prefix,
+                &Self::sparql_base,
+            ],
+        )
     }
-    
+
     /// Parses Turtle's production
     /// [6] triples ::= subject predicateObjectList | blankNodePropertyList predicateObjectList?
-    pub fn ttl_triples(&mut self, i: &'doc str) -> PResult<'doc, MownTerm<'doc>> {
-        unimplemented!()
+    ///
+    /// All parsed triples are pushed onto the context's stack.
+    pub fn ttl_triples(&mut self, i: &'doc str) -> PResult<'doc, ()> {
+        match self.ttl_subject(i) {
+            Ok((rest, s)) => {
+                let (rest, _) = multispace0(rest).map_pr()?;
+                self.ttl_predicate_object_list(rest, &s)
+                    .cut_expecting("a predicate after the subject")
+            }
+            Err(NomError::Error(_)) => {
+                let (rest, s) = self.ttl_blank_node_property_list(i)?;
+                let (after_ws, _) = multispace0(rest).map_pr()?;
+                match self.ttl_predicate_object_list(after_ws, &s) {
+                    Err(NomError::Error(_)) => Ok((rest, ())),
+                    res => res,
+                }
+            }
+            Err(e) => Err(e),
+        }
     }
-    
+
     /// Parses Turtle's production
     /// [7] predicateObjectList ::= verb objectList (';' (verb objectList)?)*
-    pub fn ttl_predicate_object_list(&mut self, i: &'doc str) -> PResult<'doc, MownTerm<'doc>> {
-        unimplemented!()
+    ///
+    /// All parsed triples are pushed onto the context's stack.
+    pub fn ttl_predicate_object_list(
+        &mut self,
+        i: &'doc str,
+        s: &MownTerm<'doc>,
+    ) -> PResult<'doc, ()> {
+        let (rest, p) = self.ttl_verb(i)?;
+        let (rest, _) = multispace0(rest).map_pr()?;
+        let (mut rest, _) = self
+            .ttl_object_list(rest, s, &p)
+            .cut_expecting("an object after the predicate")?;
+
+        loop {
+            let (r, _) = multispace0(rest).map_pr()?;
+            let r = match tag(";")(r).map_pr() {
+                Ok((r, _)) => r,
+                Err(_) => return Ok((rest, ())),
+            };
+            rest = r;
+
+            // `verb objectList` is optional after `;`
+            let (r, _) = multispace0(rest).map_pr()?;
+            match self.ttl_verb(r) {
+                Ok((r, p)) => {
+                    let (r, _) = multispace0(r).map_pr()?;
+                    let (r, _) = self
+                        .ttl_object_list(r, s, &p)
+                        .cut_expecting("an object after the predicate")?;
+                    rest = r;
+                }
+                Err(NomError::Error(_)) => {}
+                Err(e) => return Err(e),
+            };
+        }
     }
-    
+
     /// Parses Turtle's production
     /// [8] objectList ::= object (',' object)*
-    pub fn ttl_object_list(&mut self, i: &'doc str) -> PResult<'doc, MownTerm<'doc>> {
-        unimplemented!()
+    ///
+    /// All parsed triples are pushed onto the context's stack.
+    pub fn ttl_object_list(
+        &mut self,
+        i: &'doc str,
+        s: &MownTerm<'doc>,
+        p: &Iri<MownStr<'doc>>,
+    ) -> PResult<'doc, ()> {
+        let (mut rest, o) = self.ttl_object(i)?;
+        self.ctx.push_triple([s.clone(), p.clone().into(), o]);
+
+        loop {
+            let (r, _) = multispace0(rest).map_pr()?;
+            let r = match tag(",")(r).map_pr() {
+                Ok((r, _)) => r,
+                Err(_) => return Ok((rest, ())),
+            };
+            let (r, _) = multispace0(r).map_pr()?;
+            let (r, o) = self.ttl_object(r).cut_expecting("an object after `,`")?;
+            self.ctx.push_triple([s.clone(), p.clone().into(), o]);
+            rest = r;
+        }
     }
-    
+
     /// Parses Turtle's production
     /// [9] verb ::= predicate | 'a'
-    pub fn ttl_verb(&mut self, i: &'doc str) -> PResult<'doc, MownTerm<'doc>> {
-        unimplemented!()
+    pub fn ttl_verb(&mut self, i: &'doc str) -> PResult<'doc, Iri<MownStr<'doc>>> {
+        match self.ttl_predicate(i) {
+            Err(NomError::Error(_)) => {}
+            res => return res,
+        };
+
+        let (rest, _) = tag("a")(i).map_pr()?;
+        Ok((rest, rdf::iri::type_.clone_into()))
     }
 
     /// Parses Turtle's production
     /// [10] subject ::= iri | BlankNode | collection
     pub fn ttl_subject(&mut self, i: &'doc str) -> PResult<'doc, MownTerm<'doc>> {
-        unimplemented!()
+        self.alt(
+            i,
+            &[
+                &|p: &mut Self, i| p.iri(i).map(|(rest, iri)| (rest, iri.into())),
+                &|p: &mut Self, i| p.blank_node(i).map(|(rest, bn)| (rest, bn.into())),
+                &Self::ttl_collection,
+            ],
+        )
     }
 
     /// Parses Turtle's production
     /// [11] predicate ::= iri
-    pub fn ttl_predicate(&mut self, i: &'doc str) -> PResult<'doc, MownTerm<'doc>> {
-        unimplemented!()
+    pub fn ttl_predicate(&mut self, i: &'doc str) -> PResult<'doc, Iri<MownStr<'doc>>> {
+        self.iri(i)
     }
 
     /// Parses Turtle's production
     /// [12] object ::= iri | BlankNode | collection | blankNodePropertyList | literal
     pub fn ttl_object(&mut self, i: &'doc str) -> PResult<'doc, MownTerm<'doc>> {
-        unimplemented!()
+        self.alt(
+            i,
+            &[
+                &|p: &mut Self, i| p.iri(i).map(|(rest, iri)| (rest, iri.into())),
+                &|p: &mut Self, i| p.blank_node(i).map(|(rest, bn)| (rest, bn.into())),
+                &Self::ttl_collection,
+                &Self::ttl_blank_node_property_list,
+                &|p: &mut Self, i| p.literal(i).map(|(rest, lit)| (rest, lit.into())),
+            ],
+        )
     }
 
     /// Parses Turtle's production
     /// [14] blankNodePropertyList ::= '[' predicateObjectList ']'
-    pub fn ttl_blank_node_property_list(&mut self, i: &'doc str) -> PResult<'doc, MownTerm<'doc>> {
-        unimplemented!()
+    ///
+    /// # Result
+    ///
+    /// Returns the new blank node. The triples of the property list are
+    /// pushed onto the context's stack.
+    pub fn ttl_blank_node_property_list(
+        &mut self,
+        i: &'doc str,
+    ) -> PResult<'doc, MownTerm<'doc>> {
+        let (rest, _) = tag("[")(i).map_pr()?;
+        let (rest, _) = multispace0(rest).map_pr()?;
+        let bn: MownTerm<'doc> = self.ctx.new_anon_bnode().into();
+        let (rest, _) = self
+            .ttl_predicate_object_list(rest, &bn)
+            .cut_expecting("a predicate in `[ ... ]`")?;
+        let (rest, _) = multispace0(rest).map_pr()?;
+        let (rest, _) = tag("]")(rest)
+            .map_pr()
+            .cut_expecting("`]` to close the blank node property list")?;
+
+        Ok((rest, bn))
     }
 
     /// Parses Turtle's production
@@ -332,9 +344,37 @@ impl<'doc> Parser<'doc> {
     /// # Result
     ///
     /// Returns the subject of the first element in the collection. If the
-    /// collection is empty `rdf:nil` is returned.
+    /// collection is empty `rdf:nil` is returned. The `rdf:first` and
+    /// `rdf:rest` triples of the collection are pushed onto the context's
+    /// stack.
     pub fn ttl_collection(&mut self, i: &'doc str) -> PResult<'doc, MownTerm<'doc>> {
-        unimplemented!()
+        let (mut rest, _) = tag("(")(i).map_pr()?;
+        let mut objects = vec![];
+
+        loop {
+            let (r, _) = multispace0(rest).map_pr()?;
+            if let Ok((r, _)) = tag(")")(r).map_pr() {
+                rest = r;
+                break;
+            }
+            let (r, o) = self
+                .ttl_object(r)
+                .cut_expecting("an object or `)` in the collection")?;
+            objects.push(o);
+            rest = r;
+        }
+
+        let first: MownTerm<'doc> = rdf::iri::first.clone_into().into();
+        let next: MownTerm<'doc> = rdf::iri::rest.clone_into().into();
+        let mut head: MownTerm<'doc> = rdf::iri::nil.clone_into().into();
+        for o in objects.into_iter().rev() {
+            let node: MownTerm<'doc> = self.ctx.new_anon_bnode().into();
+            self.ctx.push_triple([node.clone(), first.clone(), o]);
+            self.ctx.push_triple([node.clone(), next.clone(), head]);
+            head = node;
+        }
+
+        Ok((rest, head))
     }
 }
 
@@ -346,9 +386,12 @@ impl<'doc> Parser<'doc> {
     /// [5] base ::= '@base' IRIREF '.'
     pub fn base(&mut self, i: &'doc str) -> PResult<'doc, ()> {
         let (rest, base) = base(i).map_pr()?;
+        let (_, base) = numeric_escape(base).into_pr(i, rest)?;
+        let (_, base) = Iri::new(base).into_pr(i, rest)?;
         self.ctx
             .prolog
-            .set_base(Iri::new(base).expect("Just check if absolute"))
+            .to_mut()
+            .set_base(base)
             .map(|_| ())
             .into_pr(i, rest)?;
         Ok((rest, ()))
@@ -360,7 +403,8 @@ impl<'doc> Parser<'doc> {
     /// [4] prefixID ::= '@prefix' PNAME_NS IRIREF '.'
     pub fn prefix(&mut self, i: &'doc str) -> PResult<'doc, ()> {
         let (rest, (prefix, ns)) = prefix(i).map_pr()?;
-        self.ctx.prolog.add_prefix(prefix, ns)
+        let (_, ns) = numeric_escape(ns).into_pr(i, rest)?;
+        self.ctx.prolog.to_mut().add_prefix(prefix, ns)
             .map(|_| ())
             .map_err(|_| Error::InvalidPrefix(prefix.to_string()))
             .into_pr(i, rest)
@@ -381,16 +425,19 @@ impl<'doc> Parser<'doc> {
 
     /// Parses Turtle's production
     /// [16] NumericLiteral ::= INTEGER | DECIMAL | DOUBLE
+    ///
+    /// The terminals are tried in reverse order as `INTEGER` would match the
+    /// leading digits of a `DECIMAL` or `DOUBLE`.
     pub fn numeric_literal(&mut self, i: &'doc str) -> PResult<'doc, Literal<MownStr<'doc>>> {
         alt((
-            map(parse_regex(&INTEGER), |txt| {
-                Literal::new_dt(txt, xsd::iri::integer)
+            map(parse_regex(&DOUBLE), |txt| {
+                Literal::new_dt(txt, xsd::iri::double)
             }),
             map(parse_regex(&DECIMAL), |txt| {
                 Literal::new_dt(txt, xsd::iri::decimal)
             }),
-            map(parse_regex(&DOUBLE), |txt| {
-                Literal::new_dt(txt, xsd::iri::double)
+            map(parse_regex(&INTEGER), |txt| {
+                Literal::new_dt(txt, xsd::iri::integer)
             }),
         ))(i)
         .map_pr()
@@ -400,12 +447,14 @@ impl<'doc> Parser<'doc> {
     /// [128s] RDFLiteral ::= String (LANGTAG | '^^' iri)?
     pub fn rdf_literal(&mut self, i: &'doc str) -> PResult<'doc, Literal<MownStr<'doc>>> {
         let (rest, txt) = lexical_value(i).map_pr()?;
+        let (_, txt) = string_escape(txt).into_pr(i, rest)?;
 
         if let Ok((rest, _)) = tag("^^")(rest).map_pr() {
-            let (rest, dt) = self.iri(rest)?;
+            let (rest, dt) = self.iri(rest).cut_expecting("a datatype IRI after `^^`")?;
             Ok((rest, Literal::new_dt(txt, dt)))
         } else if let Ok((rest, lang)) = parse_regex(&LANGTAG)(rest) {
-            Literal::new_lang(txt, lang).into_pr(i, rest)
+            // cut the leading '@'
+            Literal::new_lang(txt, &lang[1..]).into_pr(i, rest)
         } else {
             Ok((rest, Literal::new_dt(txt, xsd::iri::string)))
         }
@@ -428,6 +477,7 @@ impl<'doc> Parser<'doc> {
     /// [18] IRIREF ::= '<' ([^#x00-#x20<>"{}|^`\] | UCHAR)* '>'
     pub fn iriref(&mut self, i: &'doc str) -> PResult<'doc, Iri<MownStr<'doc>>> {
         let (rest, iri) = iriref(i).map_pr()?;
+        let (_, iri) = numeric_escape(iri).into_pr(i, rest)?;
         Ok((rest, self.ctx.new_iri(iri)))
     }
 
@@ -444,7 +494,7 @@ impl<'doc> Parser<'doc> {
             .or_into_pr(i, Error::InvalidPrefix(ns.to_string()), rest)?;
 
         if let Some(suffix) = suffix {
-            ns.get_iri(suffix).into_pr(i, rest)
+            ns.get_iri(local_escape(suffix)).into_pr(i, rest)
         } else {
             Ok((rest, ns.clone().into()))
         }
@@ -467,7 +517,7 @@ impl<'doc> Parser<'doc> {
     fn alt<O>(
         &mut self,
         i: &'doc str,
-        parsers: &[&dyn FnMut(&mut Self, &'doc str) -> PResult<'doc, O>],
+        parsers: &[&dyn Fn(&mut Self, &'doc str) -> PResult<'doc, O>],
     ) -> PResult<'doc, O> {
         for p in parsers {
             match p(self, i) {
@@ -481,7 +531,7 @@ impl<'doc> Parser<'doc> {
 
 /// Returns the parsed base IRI.
 /// [5s] sparqlBase ::= "BASE" IRIREF
-fn sparql_base(i: &str) -> IResult<&str, MownStr<'_>> {
+pub(crate) fn sparql_base(i: &str) -> IResult<&str, &str> {
     map(
         tuple((tag_no_case("BASE"), multispace0, iriref)),
         |(_, _, base)| base,
@@ -493,7 +543,7 @@ fn sparql_base(i: &str) -> IResult<&str, MownStr<'_>> {
 ///
 /// Returns `(prefix, iriref)` where `prefix` has no trailing `:` and `iriref`
 /// has no wrapping `<` and `>`.
-pub fn sparql_prefix(i: &str) -> IResult<&str, (&str, MownStr<'_>)> {
+pub fn sparql_prefix(i: &str) -> IResult<&str, (&str, &str)> {
     map(
         tuple((
             tag_no_case("PREFIX"),
@@ -508,7 +558,7 @@ pub fn sparql_prefix(i: &str) -> IResult<&str, (&str, MownStr<'_>)> {
 
 /// Returns the parsed base IRI.
 /// [5] base ::= '@base' IRIREF '.'
-pub fn base(i: &str) -> IResult<&str, MownStr<'_>> {
+pub fn base(i: &str) -> IResult<&str, &str> {
     map(
         tuple((tag("@base"), multispace0, iriref, multispace0, tag("."))),
         |(_, _, base, _, _)| base,
@@ -520,7 +570,7 @@ pub fn base(i: &str) -> IResult<&str, MownStr<'_>> {
 ///
 /// Returns `(prefix, iriref)` where `prefix` has no trailing `:` and `iriref`
 /// has no wrapping `<` and `>`.
-pub fn prefix(i: &str) -> IResult<&str, (&str, MownStr<'_>)> {
+pub fn prefix(i: &str) -> IResult<&str, (&str, &str)> {
     map(
         tuple((
             tag("@prefix"),
@@ -537,200 +587,272 @@ pub fn prefix(i: &str) -> IResult<&str, (&str, MownStr<'_>)> {
 
 /// Parses Turtle's production
 /// [133s] BooleanLiteral ::= 'true' | 'false'
-fn boolean_literal(i: &str) -> IResult<&str, &str> {
+pub(crate) fn boolean_literal(i: &str) -> IResult<&str, &str> {
     alt((tag("true"), tag("false")))(i)
 }
 
 /// Parses Turtle's production
 /// [17] String ::= STRING_LITERAL_QUOTE | STRING_LITERAL_SINGLE_QUOTE | STRING_LITERAL_LONG_SINGLE_QUOTE | STRING_LITERAL_LONG_QUOTE
-fn lexical_value(i: &str) -> IResult<&str, MownStr<'_>> {
-    map(
-        alt((
-            map(parse_regex(&STRING_LITERAL_LONG_QUOTE), |s| {
-                unwrap_str(s, 3)
-            }),
-            map(parse_regex(&STRING_LITERAL_QUOTE), |s| unwrap_str(s, 1)),
-            map(parse_regex(&STRING_LITERAL_LONG_SINGLE_QUOTE), |s| {
-                unwrap_str(s, 3)
-            }),
-            map(parse_regex(&STRING_LITERAL_SINGLE_QUOTE), |s| {
-                unwrap_str(s, 1)
-            }),
-        )),
-        |s| string_escape(numeric_escape(s)),
-    )(i)
+///
+/// Returns the string without quotes. Escape sequences are not resolved.
+pub(crate) fn lexical_value(i: &str) -> IResult<&str, &str> {
+    alt((
+        map(parse_regex(&STRING_LITERAL_LONG_QUOTE), |s| {
+            unwrap_str(s, 3)
+        }),
+        map(parse_regex(&STRING_LITERAL_QUOTE), |s| unwrap_str(s, 1)),
+        map(parse_regex(&STRING_LITERAL_LONG_SINGLE_QUOTE), |s| {
+            unwrap_str(s, 3)
+        }),
+        map(parse_regex(&STRING_LITERAL_SINGLE_QUOTE), |s| {
+            unwrap_str(s, 1)
+        }),
+    ))(i)
 }
 
-/// Returns the IRI without the enclosing `<` and `>`.
-fn iriref(i: &str) -> IResult<&str, MownStr<'_>> {
+/// Returns the IRI without the enclosing `<` and `>`. Escape sequences are
+/// not resolved.
+pub(crate) fn iriref(i: &str) -> IResult<&str, &str> {
     map_opt(parse_regex(&IRIREF), |s| {
         if s.len() < 2 {
             None
         } else {
-            Some(numeric_escape(unwrap_str(s, 1)))
+            Some(unwrap_str(s, 1))
         }
     })(i)
 }
 
 /// Returns the prefix without ':' at the end and the suffix.
-fn pname_ln_split(i: &str) -> IResult<&str, (&str, Option<&str>)> {
+pub(crate) fn pname_ln_split(i: &str) -> IResult<&str, (&str, Option<&str>)> {
     let (rest, full) = pname_ln(i)?;
-    let mut parts = full.split(':');
+    // the local part may contain further ':'
+    let mut parts = full.splitn(2, ':');
     Ok((rest, (parts.next().unwrap(), parts.next())))
 }
 
 /// Returns the prefix without ':' at the end.
-fn pname_ns(i: &str) -> IResult<&str, &str> {
+pub(crate) fn pname_ns(i: &str) -> IResult<&str, &str> {
     parse_regex(&PNAME_NS)(i).map(|(rest, prefix)| (rest, &prefix[..prefix.len() - 1]))
 }
 
 /// Return none if successful.
-fn anon(i: &str) -> IResult<&str, Option<&str>> {
+pub(crate) fn anon(i: &str) -> IResult<&str, Option<&str>> {
     parse_regex(&ANON)(i).map(|(rest, _)| (rest, None))
 }
 
 #[cfg(test)]
 mod test {
-    // use super::*;
-    // use sophia::ns::xsd;
-    // use sophia::term::LiteralKind;
-    // use test_case::test_case;
-
-    // fn ctx<'a>() -> RefContext<'a> {
-    //     RefCell::new(Context::with_default_prefixes())
-    // }
-
-    // #[test]
-    // fn check_triples() -> std::result::Result<(), Box<dyn std::error::Error>> {
-    //     let check1 = Term::new_literal_dt_unchecked("45", Term::from(&xsd::integer));
-    //     let check2 = Term::new_literal_dt_unchecked("false", Term::from(&xsd::boolean));
-    //     let objects1 = vec![check1, Term::from(&rdf::type_)];
-    //     let objects2 = vec![check2];
-    //     let po1 = PoList::new(Term::from(&rdf::value), objects1);
-    //     let po2 = PoList::new(Term::from(&rdf::type_), objects2);
-    //     let pos = vec![po1, po2];
-    //     let check3 = unsafe { Term::new_bnode_unchecked("anon0") };
-    //     let res = SpoList::new(check3, pos);
-
-    //     let ctx = ctx();
-    //     let check = "[] rdf:value \"45\"^^xsd:integer, rdf:type  ;  a  false  rest";
-    //     let (rest, list) = triples(check, &ctx).unwrap();
-    //     assert_eq!(3, list.iter().count());
-    //     assert_eq!("  rest", rest);
-    //     assert_eq!(res, list);
-
-    //     Ok(())
-    // }
-
-    // #[test]
-    // fn check_predicate_object_list() -> std::result::Result<(), Box<dyn std::error::Error>> {
-    //     let check1 = Term::new_literal_dt_unchecked("45", Term::from(&xsd::integer));
-    //     let check2 = Term::new_literal_dt_unchecked("false", Term::from(&xsd::boolean));
-    //     let objects1 = vec![check1, Term::from(&rdf::type_)];
-    //     let objects2 = vec![check2];
-    //     let po1 = PoList::new(Term::from(&rdf::value), objects1);
-    //     let po2 = PoList::new(Term::from(&rdf::type_), objects2);
-    //     let res = vec![po1, po2];
-
-    //     let ctx = ctx();
-    //     let check = "rdf:value \"45\"^^xsd:integer, rdf:type  ;  a  false  rest";
-    //     let (rest, list) = predicate_object_list(check, &ctx).unwrap();
-    //     assert_eq!(2, list.len());
-    //     assert_eq!(
-    //         3,
-    //         list.iter().map(|objects| objects.iter()).flatten().count()
-    //     );
-    //     assert_eq!("  rest", rest);
-    //     assert_eq!(res, list);
-
-    //     Ok(())
-    // }
-
-    // #[test]
-    // fn check_object_list() -> std::result::Result<(), Box<dyn std::error::Error>> {
-    //     let ctx = ctx();
-    //     let check = "\"45\"^^xsd:integer, rdf:type  ,  false  rest";
-    //     let (rest, list) = object_list(check, &ctx).unwrap();
-    //     assert_eq!(3, list.len());
-    //     assert_eq!("  rest", rest);
-    //     let check1 = Term::new_literal_dt_unchecked("45", xsd::integer.clone());
-    //     let check3 = Term::new_literal_dt_unchecked("false", xsd::boolean.clone());
-    //     assert_eq!(check1, list[0]);
-    //     assert_eq!(rdf::type_, list[1]);
-    //     assert_eq!(check3, list[2]);
-
-    //     Ok(())
-    // }
-
-    // #[test]
-    // fn check_iri() -> std::result::Result<(), Box<dyn std::error::Error>> {
-    //     let ctx = ctx();
-    //     let (rest, iri) = iri("<http://www.w3.org/1999/02/22-rdf-syntax-ns#>  rest", &ctx)?;
-    //     assert_eq!("  rest", rest);
-    //     assert_eq!("http://www.w3.org/1999/02/22-rdf-syntax-ns#", iri.value());
-
-    //     Ok(())
-    // }
-
-    // #[test]
-    // fn check_rdf_literal() -> std::result::Result<(), Box<dyn std::error::Error>> {
-    //     let ctx = ctx();
-    //     let check = "\"45\"^^xsd:integer  rest";
-    //     let (rest, term) = rdf_literal(check, &ctx).unwrap();
-    //     assert_eq!("  rest", rest);
-    //     match term {
-    //         Term::Literal(txt, LiteralKind::Datatype(dt)) => {
-    //             assert_eq!("45", txt);
-    //             assert_eq!(xsd::integer, dt);
-    //         }
-    //         _ => panic!("Wrong parsed"),
-    //     };
-
-    //     let check = "\"lorem ipsum\"   rest";
-    //     let (rest, term) = rdf_literal(check, &ctx).unwrap();
-    //     assert_eq!("   rest", rest);
-    //     match term {
-    //         Term::Literal(txt, LiteralKind::Datatype(dt)) => {
-    //             assert_eq!("lorem ipsum", txt);
-    //             assert_eq!(xsd::string, dt);
-    //         }
-    //         _ => panic!("Wrong parsed"),
-    //     };
-
-    //     let check = "\"hello\"@en  rest";
-    //     let (rest, term) = rdf_literal(check, &ctx).unwrap();
-    //     assert_eq!("  rest", rest);
-    //     match term {
-    //         Term::Literal(txt, LiteralKind::Lang(lang)) => {
-    //             assert_eq!("hello", txt);
-    //             assert_eq!("en", lang);
-    //         }
-    //         _ => panic!("Wrong parsed"),
-    //     };
-
-    //     Ok(())
-    // }
-
-    // #[test_case("\"quote\"  rest" => ("  rest", "quote") ; "quote")]
-    // #[test_case("'quote'  rest" => ("  rest", "quote") ; "single")]
-    // #[test_case("\"\"\"quote\"\"\"  rest" => ("  rest", "quote") ; "long quote")]
-    // #[test_case("'''quote'''  rest" => ("  rest", "quote") ; "long single quote")]
-    // fn check_string(i: &str) -> (&str, &str) {
-    //     let (rest, string) = string(i).unwrap();
-    //     if let Cow::Borrowed(string) = string {
-    //         (rest, string)
-    //     } else {
-    //         panic!()
-    //     }
-    // }
-
-    // #[test]
-    // fn check_blank_node() -> std::result::Result<(), Box<dyn std::error::Error>> {
-    //     let ctx = ctx();
-    //     let (rest, res) = blank_node("_:example  rest", &ctx)?;
-    //     assert_eq!("  rest", rest);
-    //     assert_eq!("example", res.value());
-
-    //     Ok(())
-    // }
+    use super::*;
+    use sophia::term::Term;
+    use test_case::test_case;
+
+    fn parser(doc: &str) -> Parser<'_> {
+        let mut parser = Parser::new(doc);
+        parser.ctx.prolog.to_mut().add_default_prefixes();
+        parser
+    }
+
+    #[test]
+    fn check_triples() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let check = "[] rdf:value \"45\"^^xsd:integer, rdf:type  ;  a  false  rest";
+        let mut p = parser(check);
+        let (rest, _) = p.ttl_triples(check)?;
+        assert_eq!("  rest", rest);
+        assert_eq!(3, p.ctx.triple_stack.len());
+
+        let check = "[ rdf:value 42 ]  rest";
+        let mut p = parser(check);
+        let (rest, _) = p.ttl_triples(check)?;
+        assert_eq!("  rest", rest);
+        assert_eq!(1, p.ctx.triple_stack.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_predicate_object_list() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let s: MownTerm = rdf::iri::nil.clone_into().into();
+        let check = "rdf:value \"45\"^^xsd:integer, rdf:type  ;  a  false ;  rest";
+        let mut p = parser(check);
+        let (rest, _) = p.ttl_predicate_object_list(check, &s)?;
+        assert_eq!("  rest", rest);
+        assert_eq!(3, p.ctx.triple_stack.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_object_list() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let s: MownTerm = rdf::iri::nil.clone_into().into();
+        let pred: Iri<MownStr> = rdf::iri::value.clone_into();
+        let check = "\"45\"^^xsd:integer, rdf:type  ,  false  rest";
+        let mut p = parser(check);
+        let (rest, _) = p.ttl_object_list(check, &s, &pred)?;
+        assert_eq!("  rest", rest);
+        let objects: Vec<_> = p.ctx.triple_stack.iter().map(|[_, _, o]| o.clone()).collect();
+        let check1: MownTerm = Literal::new_dt("45", xsd::iri::integer).into();
+        let check2: MownTerm = rdf::iri::type_.clone_into().into();
+        let check3: MownTerm = Literal::new_dt("false", xsd::iri::boolean).into();
+        assert_eq!(objects, vec![check1, check2, check3]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_collection() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let check = "( 1 ( ) [ rdf:value 2 ] )  rest";
+        let mut p = parser(check);
+        let (rest, head) = p.ttl_collection(check)?;
+        assert_eq!("  rest", rest);
+        assert!(matches!(head, Term::BNode(_)));
+        // 3 elements => 6 triples + 1 from the property list
+        assert_eq!(7, p.ctx.triple_stack.len());
+
+        let check = "()  rest";
+        let mut p = parser(check);
+        let (rest, head) = p.ttl_collection(check)?;
+        assert_eq!("  rest", rest);
+        assert_eq!(head, Term::from(rdf::iri::nil.clone_into::<MownStr>()));
+        assert!(p.ctx.triple_stack.is_empty());
+
+        let check = "( 1 2  rest";
+        let mut p = parser(check);
+        assert!(matches!(p.ttl_collection(check), Err(NomError::Failure(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_iri() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let check = "<http://www.w3.org/1999/02/22-rdf-syntax-ns#>  rest";
+        let mut p = parser(check);
+        let (rest, iri) = p.iri(check)?;
+        assert_eq!("  rest", rest);
+        assert_eq!("http://www.w3.org/1999/02/22-rdf-syntax-ns#", &*iri.value());
+
+        let check = "rdf:type  rest";
+        let mut p = parser(check);
+        let (rest, iri) = p.iri(check)?;
+        assert_eq!("  rest", rest);
+        assert_eq!(&*rdf::iri::type_.value(), &*iri.value());
+
+        let check = "unknown:type  rest";
+        let mut p = parser(check);
+        assert!(matches!(p.iri(check), Err(NomError::Failure(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_rdf_literal() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let check = "\"45\"^^xsd:integer  rest";
+        let mut p = parser(check);
+        let (rest, lit) = p.rdf_literal(check)?;
+        assert_eq!("  rest", rest);
+        assert_eq!(lit, Literal::new_dt("45", xsd::iri::integer));
+
+        let check = "\"lorem ipsum\"   rest";
+        let mut p = parser(check);
+        let (rest, lit) = p.rdf_literal(check)?;
+        assert_eq!("   rest", rest);
+        assert_eq!(lit, Literal::new_dt("lorem ipsum", xsd::iri::string));
+
+        let check = "\"hello\"@en  rest";
+        let mut p = parser(check);
+        let (rest, lit) = p.rdf_literal(check)?;
+        assert_eq!("  rest", rest);
+        assert_eq!(lit, Literal::new_lang("hello", "en")?);
+
+        Ok(())
+    }
+
+    #[test_case("42 rest" => (" rest", "42".to_owned()) ; "integer")]
+    #[test_case("-4.2 rest" => (" rest", "-4.2".to_owned()) ; "decimal")]
+    #[test_case("4.2e1 rest" => (" rest", "4.2e1".to_owned()) ; "double")]
+    #[test_case("42. rest" => (". rest", "42".to_owned()) ; "end of statement")]
+    fn check_numeric_literal(i: &str) -> (&str, String) {
+        let (rest, lit) = parser(i).numeric_literal(i).unwrap();
+        (rest, lit.txt().as_ref().to_owned())
+    }
+
+    #[test_case("\"quote\"  rest" => ("  rest", "quote".to_owned()) ; "quote")]
+    #[test_case("'quote'  rest" => ("  rest", "quote".to_owned()) ; "single")]
+    #[test_case("\"\"\"quote\"\"\"  rest" => ("  rest", "quote".to_owned()) ; "long quote")]
+    #[test_case("'''quote'''  rest" => ("  rest", "quote".to_owned()) ; "long single quote")]
+    #[test_case(r#""a\tb"  rest"# => ("  rest", "a\\tb".to_owned()) ; "escape unresolved")]
+    fn check_string(i: &str) -> (&str, String) {
+        let (rest, string) = lexical_value(i).unwrap();
+        (rest, string.to_string())
+    }
+
+    #[test_case(r"no escape" => "no escape".to_owned() ; "none")]
+    #[test_case(r"a\tb\nc" => "a\tb\nc".to_owned() ; "whitespace")]
+    #[test_case(r#"\"\'\\"# => "\"'\\".to_owned() ; "quotes and backslash")]
+    #[test_case(r"\b\f\r" => "\u{8}\u{c}\r".to_owned() ; "control")]
+    #[test_case(r"\u00e9t\u00E9" => "été".to_owned() ; "small uchar")]
+    #[test_case(r"\U0001F600" => "\u{1F600}".to_owned() ; "big uchar")]
+    #[test_case(r"\\u0041" => "\\u0041".to_owned() ; "escaped backslash before u")]
+    fn check_string_escape(i: &str) -> String {
+        string_escape(i).unwrap().to_string()
+    }
+
+    #[test_case(r"\uD800" ; "high surrogate")]
+    #[test_case(r"\uDFFF" ; "low surrogate")]
+    #[test_case(r"\U00110000" ; "beyond unicode")]
+    #[test_case(r"\u00" ; "incomplete")]
+    #[test_case(r"\a" ; "unknown echar")]
+    fn check_string_escape_invalid(i: &str) {
+        assert!(string_escape(i).is_err());
+    }
+
+    #[test]
+    fn check_escape_borrows() {
+        assert!(matches!(string_escape("plain"), Ok(MownStr::Ref(_))));
+        assert!(matches!(string_escape("a\\tb"), Ok(MownStr::Own(_))));
+        assert!(matches!(numeric_escape("http://ex/"), Ok(MownStr::Ref(_))));
+    }
+
+    #[test_case(r"http://ex/\u00E9" => Some("http://ex/é".to_owned()) ; "uchar")]
+    #[test_case(r"http://ex/a\tb" => None ; "no echar in IRIs")]
+    fn check_numeric_escape(i: &str) -> Option<String> {
+        numeric_escape(i).ok().map(|iri| iri.to_string())
+    }
+
+    #[test]
+    fn check_escaped_terms() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let check = "<http://ex/\\u00E9>  rest";
+        let mut p = parser(check);
+        let (rest, iri) = p.iri(check)?;
+        assert_eq!("  rest", rest);
+        assert_eq!("http://ex/é", &*iri.value());
+
+        let check = "'a\\tb'@en  rest";
+        let mut p = parser(check);
+        let (rest, lit) = p.rdf_literal(check)?;
+        assert_eq!("  rest", rest);
+        assert_eq!(lit, Literal::new_lang("a\tb", "en")?);
+
+        let check = "rdf:a\\.b  rest";
+        let mut p = parser(check);
+        let (rest, iri) = p.iri(check)?;
+        assert_eq!("  rest", rest);
+        assert_eq!(format!("{}a.b", rdf::PREFIX), &*iri.value());
+
+        let check = "'\\uD800'  rest";
+        let mut p = parser(check);
+        assert!(matches!(p.rdf_literal(check), Err(NomError::Failure(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_blank_node() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let check = "_:example  rest";
+        let mut p = parser(check);
+        let (rest, bn) = p.blank_node(check)?;
+        assert_eq!("  rest", rest);
+        assert_eq!("example", bn.as_ref());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,434 @@
+//! Parse Turtle from a `BufRead` without loading the whole document.
+//!
+//! The input is read chunk by chunk. All complete statements of the buffered
+//! input are parsed and the consumed part is dropped afterwards. Therefore,
+//! only the unconsumed tail of the input and the `Prolog` are kept in memory.
+//!
+//! An incomplete statement is parsed again only after the buffer has doubled
+//! in size. Thus, long statements like large collections are still parsed in
+//! linear time.
+//!
+//! As the input is dropped after parsing the returned terms own their data.
+
+use super::{skip_statement, terminals::multispace0, to_box, Parser};
+use crate::error::{Error, Result};
+use crate::parse::{locate_err, Context, Position};
+use crate::{Prolog, Turtle};
+use sophia::term::{iri::Iri, mown_str::MownStr, BoxTerm};
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
+use std::str;
+
+/// Number of bytes that must follow an error before it is reported.
+///
+/// If a statement can not be parsed this may be due to the fact that it is
+/// not yet completely read. Therefore, more input is read until at least this
+/// number of bytes follows the position of the error or the end of the input
+/// is reached.
+///
+/// _Note:_ A string literal that spans a chunk boundary is completed by
+/// reading more input. Only literals longer than this limit are reported as
+/// an error in this case.
+pub const LOOKAHEAD: usize = 1 << 16;
+
+/// A Turtle parser that reads its document from a `BufRead`.
+///
+/// Like `Parser` this yields the triples statement per statement. However,
+/// the triples own their terms.
+pub struct StreamParser<B> {
+    /// The source of the document.
+    src: B,
+    /// Read but not yet parsed part of the document.
+    buf: String,
+    /// Bytes of an UTF-8 character that is split between two chunks.
+    partial: Vec<u8>,
+    /// true if `src` is exhausted.
+    eof: bool,
+    /// Base and prefixes of the parsed part of the document.
+    prolog: Prolog<'static, Turtle>,
+    /// Number of parsed blank nodes. Used for naming anonymous nodes.
+    bnode_cnt: usize,
+    /// Parsed triples and errors that are not yet returned.
+    results: VecDeque<Result<[BoxTerm; 3]>>,
+    /// Position of the start of `buf` within the document.
+    consumed: Position,
+    /// Length `buf` must reach before it is parsed again.
+    retry_at: usize,
+    /// true if the parser failed once or if it is at EOF.
+    ///
+    /// In both cases the `next() = None`.
+    end_or_failed: bool,
+    /// Continue parsing after an invalid statement.
+    lenient: bool,
+    /// true if the rest of an invalid statement must be skipped.
+    skipping: bool,
+}
+
+impl<B: BufRead> StreamParser<B> {
+    /// Creates a new parser reading from `src`.
+    pub fn new(src: B) -> Self {
+        Self {
+            src,
+            buf: String::new(),
+            partial: vec![],
+            eof: false,
+            prolog: Prolog::default(),
+            bnode_cnt: 0,
+            results: VecDeque::new(),
+            consumed: Position {
+                offset: 0,
+                line: 1,
+                column: 1,
+            },
+            retry_at: 0,
+            end_or_failed: false,
+            lenient: false,
+            skipping: false,
+        }
+    }
+    /// A new parser with a pre-set base IRI to resolve `iri` productions.
+    ///
+    /// _Note:_ If the document contains an own `base` directive the pre-set
+    /// value is overridden.
+    pub fn with_base(src: B, base: Iri<MownStr<'static>>) -> Result<Self> {
+        let mut parser = Self::new(src);
+        parser.prolog.set_base(base)?;
+        Ok(parser)
+    }
+    /// Sets the base IRI given as string.
+    ///
+    /// An invalid base IRI is returned as the first error of the parser.
+    pub(crate) fn with_base_str(mut self, base: &str) -> Self {
+        let res = Iri::new(MownStr::from(base.to_string()))
+            .map_err(Error::from)
+            .and_then(|base| self.prolog.set_base(base).map_err(Error::from));
+        if let Err(err) = res {
+            self.results.push_back(Err(err));
+        }
+        self
+    }
+    /// Sets if the parser continues after an invalid statement.
+    ///
+    /// See `Parser::set_lenient()`.
+    pub fn set_lenient(&mut self, lenient: bool) -> &mut Self {
+        self.lenient = lenient;
+        self
+    }
+    /// Appends the next chunk of `src` to the buffer.
+    fn fill(&mut self) -> io::Result<()> {
+        let chunk = loop {
+            match self.src.fill_buf() {
+                Ok(chunk) => break chunk,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        };
+        let len = chunk.len();
+        if len == 0 {
+            self.eof = true;
+            if !self.partial.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "document ends within an UTF-8 character",
+                ));
+            }
+            return Ok(());
+        }
+        self.partial.extend_from_slice(chunk);
+        self.src.consume(len);
+
+        let valid = match str::from_utf8(&self.partial) {
+            Ok(_) => self.partial.len(),
+            // only the last character is incomplete
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        };
+        let text = str::from_utf8(&self.partial[..valid]).expect("checked before");
+        self.buf.push_str(text);
+        self.partial.drain(..valid);
+        Ok(())
+    }
+    /// Parses all complete statements of the buffer.
+    ///
+    /// The triples of the statements are stored and the consumed input is
+    /// dropped. A statement is complete if it is followed by further input or
+    /// if the end of the input is reached.
+    fn parse_buffered(&mut self) {
+        let Self {
+            buf,
+            eof,
+            prolog,
+            bnode_cnt,
+            results,
+            consumed,
+            retry_at,
+            lenient,
+            skipping,
+            ..
+        } = self;
+        let eof = *eof;
+
+        // the prolog is only copied if a directive changes it
+        let ctx = Context {
+            prolog: Cow::Borrowed(&*prolog),
+            bnode_cnt: *bnode_cnt,
+            triple_stack: VecDeque::new(),
+        };
+        let mut parser = Parser::with_context(buf, ctx);
+        let mut current = buf.as_str();
+
+        loop {
+            if *skipping {
+                match skip_statement(current, eof) {
+                    Some(rest) => current = rest,
+                    None if eof => current = &current[current.len()..],
+                    None => break,
+                }
+                *skipping = false;
+            }
+
+            // multispace0 never fails
+            let (rest, _) = multispace0(current).unwrap();
+            if rest.is_empty() {
+                if eof {
+                    current = rest;
+                }
+                // otherwise, a comment may be continued
+                break;
+            }
+            current = rest;
+
+            match parser.ttl_statement(current) {
+                // The final `.` of triples may be part of a longer token that
+                // is continued in the next chunk. Directives are complete.
+                Ok((rest, _)) if !eof && rest.is_empty() && !parser.ctx.triple_stack.is_empty() => {
+                    parser.ctx.triple_stack.clear();
+                    break;
+                }
+                Ok((rest, _)) => {
+                    current = rest;
+                    *bnode_cnt = parser.ctx.bnode_cnt;
+                    results.extend(parser.ctx.triple_stack.drain(..).map(|t| Ok(to_box(t))));
+                }
+                Err(e) => {
+                    parser.ctx.triple_stack.clear();
+                    let e = locate_err(buf, e);
+                    let definite = match &e {
+                        Error::Parser { offset, .. } => eof || buf.len() - offset >= LOOKAHEAD,
+                        _ => true,
+                    };
+                    if !definite {
+                        break;
+                    }
+                    results.push_back(Err(relocate(e, consumed)));
+                    if !*lenient {
+                        break;
+                    }
+                    *skipping = true;
+                }
+            }
+        }
+
+        let len = buf.len() - current.len();
+        if let Cow::Owned(changed) = parser.ctx.prolog {
+            *prolog = changed.into_owned();
+        }
+        advance(consumed, &buf[..len]);
+        buf.drain(..len);
+        *retry_at = 2 * buf.len();
+    }
+}
+
+impl<B: BufRead> Iterator for StreamParser<B> {
+    type Item = Result<[BoxTerm; 3]>;
+
+    /// Returns parsed triples.
+    ///
+    /// Input is only read if all triples of the already read input are
+    /// returned.
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.end_or_failed {
+                return None;
+            } else if let Some(res) = self.results.pop_front() {
+                match &res {
+                    Err(Error::Parser { .. }) if self.lenient => {}
+                    Err(_) => self.end_or_failed = true,
+                    Ok(_) => {}
+                }
+                return Some(res);
+            } else if self.eof {
+                // everything was parsed
+                self.end_or_failed = true;
+                return None;
+            }
+
+            if self.buf.len() >= self.retry_at {
+                self.parse_buffered();
+            }
+            if self.results.is_empty() {
+                if let Err(e) = self.fill() {
+                    self.results.push_back(Err(e.into()));
+                } else if self.eof {
+                    self.parse_buffered();
+                }
+            }
+        }
+    }
+}
+
+/// Moves `pos` behind `text`.
+fn advance(pos: &mut Position, text: &str) {
+    pos.offset += text.len();
+    match text.rfind('\n') {
+        Some(idx) => {
+            pos.line += text.matches('\n').count();
+            pos.column = text[idx + 1..].chars().count() + 1;
+        }
+        None => pos.column += text.chars().count(),
+    }
+}
+
+/// Makes the position of a parser error relative to the whole document.
+///
+/// `consumed` is the position where the parsed buffer started.
+fn relocate(err: Error, consumed: &Position) -> Error {
+    match err {
+        Error::Parser {
+            offset,
+            line,
+            column,
+            kind,
+        } => Error::Parser {
+            offset: consumed.offset + offset,
+            line: consumed.line + line - 1,
+            column: if line == 1 {
+                consumed.column + column - 1
+            } else {
+                column
+            },
+            kind,
+        },
+        err => err,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse::Error as ParserErrorKind;
+    use test_case::test_case;
+
+    const DOC: &str = r#"@prefix : <http://example.org/> .
+    # a comment with a multi-byte character: ä
+    :s :p "äöü", """long
+    string""" ;
+        :q ( 1 2.5 [ :r :o ] ) .
+    BASE <http://example.org/base/>
+    <s> :p 1.5e3, :o.b .
+    "#;
+
+    fn expected() -> Vec<String> {
+        Parser::new(DOC)
+            .map(|res| {
+                let [s, p, o] = res.unwrap();
+                format!("{} {} {}", s, p, o)
+            })
+            .collect()
+    }
+
+    #[test_case(1 ; "single bytes")]
+    #[test_case(3 ; "small chunks")]
+    #[test_case(8 * 1024 ; "whole document")]
+    fn check_chunks(capacity: usize) {
+        let src = io::BufReader::with_capacity(capacity, DOC.as_bytes());
+        let parsed: Vec<_> = StreamParser::new(src)
+            .map(|res| {
+                let [s, p, o] = res.unwrap();
+                format!("{} {} {}", s, p, o)
+            })
+            .collect();
+        assert_eq!(parsed, expected());
+    }
+
+    #[test]
+    fn check_large_collection() {
+        let items = (0..10_000).map(|i| i.to_string()).collect::<Vec<_>>();
+        let doc = format!(
+            "@prefix : <http://example.org/> .\n:s :p ( {} ) .\n:s :q :o .\n",
+            items.join(" ")
+        );
+        let src = io::BufReader::with_capacity(16, doc.as_bytes());
+        let parsed = StreamParser::new(src).collect::<Result<Vec<_>>>().unwrap();
+
+        assert_eq!(parsed.len(), 2 * items.len() + 2);
+        assert_eq!(
+            parsed.last().unwrap()[2].value().to_string(),
+            "http://example.org/o"
+        );
+    }
+
+    #[test]
+    fn check_error_position() {
+        let doc = "@prefix : <http://ex/> .\n:s :p :o .\n:s :p unknown:o .\n:s :p :o2 .\n";
+        let src = io::BufReader::with_capacity(4, doc.as_bytes());
+        let results: Vec<_> = StreamParser::new(src).collect();
+
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        match &results[1] {
+            Err(Error::Parser {
+                offset,
+                line,
+                column,
+                kind: ParserErrorKind::InvalidPrefix(_),
+            }) => {
+                assert_eq!(*offset, doc.find("unknown").unwrap());
+                assert_eq!((*line, *column), (3, 7));
+            }
+            res => panic!("Unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn check_missing_dot() {
+        let doc = "<http://ex/s> <http://ex/p> <http://ex/o>";
+        let src = io::BufReader::with_capacity(4, doc.as_bytes());
+        let mut parser = StreamParser::new(src);
+        assert!(matches!(parser.next(), Some(Err(Error::Parser { .. }))));
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn check_invalid_utf8() {
+        let doc: &[u8] = b"<http://ex/s> <http://ex/p> \"\xff\" .";
+        let mut parser = StreamParser::new(doc);
+        assert!(matches!(parser.next(), Some(Err(Error::FromIo(_)))));
+        assert!(parser.next().is_none());
+    }
+
+    #[test]
+    fn check_lenient() {
+        let doc = "@prefix : <http://ex/> .\n:s :p :o1 .\n:s :p unknown:o .\n:s :p \"x . y\" ; :q .\n:s :p :o2 .\n";
+        let src = io::BufReader::with_capacity(4, doc.as_bytes());
+        let mut parser = StreamParser::new(src);
+        parser.set_lenient(true);
+        let results: Vec<_> = parser
+            .map(|res| match res {
+                Ok([_, _, o]) => o.value().to_string(),
+                Err(Error::Parser { line, .. }) => format!("error in line {}", line),
+                Err(e) => panic!("Unexpected error: {}", e),
+            })
+            .collect();
+        assert_eq!(
+            results,
+            vec![
+                "http://ex/o1",
+                "error in line 3",
+                "error in line 4",
+                "http://ex/o2",
+            ]
+        );
+    }
+}
@@ -3,7 +3,7 @@
 //! # Escape
 //!
 //! All terminals perform no escape resolution, meaning `"\u0020"` will be
-//! recognized but not resolved.
+//! recognized but not resolved. Resolution is done by the productions.
 //!
 //! # Provided
 //!
@@ -16,7 +16,7 @@ use crate::parse::util::parse_regex;
 use lazy_static::lazy_static;
 use nom::branch::alt;
 use nom::bytes::complete::tag;
-use nom::combinator::{opt, recognize};
+use nom::combinator::recognize;
 use nom::multi::many0;
 use nom::sequence::tuple;
 use nom::IResult;
@@ -70,20 +70,20 @@ lazy_static! {
     /// Production of STRING_LITERAL_LONG_SINGLE_QUOTE according to the [Turtle spec](https://www.w3.org/TR/turtle/#sec-grammar).
     pub static ref STRING_LITERAL_LONG_SINGLE_QUOTE: Regex = Regex::new(r#"^'''((('|'')?([^'\\]|(\\[tbnrf"'\\])|(\\u[[:xdigit:]]{4})|(\\U[[:xdigit:]]{8}))))*'''"#).unwrap();
     /// Production of UCHAR according to the [Turtle spec](https://www.w3.org/TR/turtle/#sec-grammar).
-    pub static ref UCHAR: Regex = Regex::new(r#"^(\\u[[:xdigit:]]{4})|(\\U[[:xdigit:]]{8})"#).unwrap();
+    pub static ref UCHAR: Regex = Regex::new(r#"^((\\u[[:xdigit:]]{4})|(\\U[[:xdigit:]]{8}))"#).unwrap();
     /// Production of ECHAR according to the [Turtle spec](https://www.w3.org/TR/turtle/#sec-grammar).
     pub static ref ECHAR: Regex = Regex::new(r#"^\\[tbnrf"'\\]"#).unwrap();
     /// Production of WS according to the [Turtle spec](https://www.w3.org/TR/turtle/#sec-grammar).
     /// Parses comments as well
-    pub static ref WS: Regex = Regex::new(r#"^[ \t\n\r]|(#[^\n]*\n)"#).unwrap();
+    pub static ref WS: Regex = Regex::new(r#"^([ \t\n\r]|(#[^\n]*(\n|$)))"#).unwrap();
     /// Parses ^ WS?
-    pub static ref WS_MANY1: Regex = Regex::new(r#"^([ \t\n\r]|(#[^\n]*\n))+"#).unwrap();
+    pub static ref WS_MANY1: Regex = Regex::new(r#"^([ \t\n\r]|(#[^\n]*(\n|$)))+"#).unwrap();
     /// Parses ^ WS*
-    pub static ref WS_MANY0: Regex = Regex::new(r#"^([ \t\n\r]|(#[^\n]*\n))*"#).unwrap();
+    pub static ref WS_MANY0: Regex = Regex::new(r#"^([ \t\n\r]|(#[^\n]*(\n|$)))*"#).unwrap();
     /// Production of ANON according to the [Turtle spec](https://www.w3.org/TR/turtle/#sec-grammar).
     pub static ref ANON: Regex = Regex::new(r#"^\[[ \t\n\r]*\]"#).unwrap();
     /// Production of PLX according to the [Turtle spec](https://www.w3.org/TR/turtle/#sec-grammar).
-    pub static ref PLX: Regex = Regex::new(r#"^(%[[:xdigit:]]{2})|(\\[-_~\.!\$&'#\(\)\*\+,;=/\?@%])"#).unwrap();
+    pub static ref PLX: Regex = Regex::new(r#"^((%[[:xdigit:]]{2})|(\\[-_~\.!\$&'#\(\)\*\+,;=/\?@%]))"#).unwrap();
     /// Production of PERCENT according to the [Turtle spec](https://www.w3.org/TR/turtle/#sec-grammar).
     pub static ref PERCENT: Regex = Regex::new(r#"^%[[:xdigit:]]{2}"#).unwrap();
     /// Production of HEX according to the [Turtle spec](https://www.w3.org/TR/turtle/#sec-grammar).
@@ -104,19 +104,19 @@ pub fn pname_ln(i: &str) -> IResult<&str, &str> {
 /// Parses Turtle's rule
 /// [141s] BLANK_NODE_LABEL ::= '_:' (PN_CHARS_U | [[:digit:]]) ((PN_CHARS | '.')* PN_CHARS)?
 pub fn blank_node_label(i: &str) -> IResult<&str, Option<&str>> {
-    recognize(tuple((
+    let (_, label) = recognize(tuple((
         tag("_:"),
         alt((parse_regex(&PN_CHARS_U), parse_regex(&DIGIT))),
         many0(alt((parse_regex(&PN_CHARS), tag(".")))),
-        opt(parse_regex(&PN_CHARS)),
-    )))(i)
-    .map(|(rest, id)| (rest, Some(id)))
+    )))(i)?;
+    let label = trim_trailing_dots(label);
+    Ok((&i[label.len()..], Some(label)))
 }
 
 /// Parses Turtle's rule
 /// [168s] PN_LOCAL ::= (PN_CHARS_U | ':' | [[:digit:]] | PLX) ((PN_CHARS | '.' | ':' | PLX)* (PN_CHARS | ':' | PLX))?
 pub fn pn_local(i: &str) -> IResult<&str, &str> {
-    recognize(tuple((
+    let (_, local) = recognize(tuple((
         alt((
             parse_regex(&PN_CHARS_U),
             tag(":"),
@@ -129,8 +129,22 @@ pub fn pn_local(i: &str) -> IResult<&str, &str> {
             tag(":"),
             parse_regex(&PLX),
         ))),
-        opt(alt((parse_regex(&PN_CHARS), tag(":"), parse_regex(&PLX)))),
-    )))(i)
+    )))(i)?;
+    let local = trim_trailing_dots(local);
+    Ok((&i[local.len()..], local))
+}
+
+/// Removes unescaped `.` at the end of a name.
+///
+/// Names must not end with a `.` as this would clash with the end of a
+/// statement, e.g. `:s :p :o.`. As `many0()` parsers are greedy, the dots
+/// are cut afterwards.
+fn trim_trailing_dots(name: &str) -> &str {
+    let mut name = name;
+    while name.ends_with('.') && !name[..name.len() - 1].ends_with('\\') {
+        name = &name[..name.len() - 1];
+    }
+    name
 }
 
 /// Parses at least one whitespace (including comments).
@@ -271,6 +285,7 @@ mod test {
     #[test_case("_:example  rest" => Ok(("  rest", "_:example")) ; "start alpha")]
     #[test_case("_:0  rest" => Ok(("  rest", "_:0")) ; "start num")]
     #[test_case("_:_  rest" => Ok(("  rest", "_:_")) ; "start under")]
+    #[test_case("_:a.b. rest" => Ok((". rest", "_:a.b")) ; "trailing dot")]
     fn check_blank_node_label(i: &str) -> IResult<&str, &str> {
         blank_node_label(i).map(|(rest, id)| (rest, id.unwrap()))
     }
@@ -279,12 +294,14 @@ mod test {
     #[test_case("# some comment \n" => true ; "comment only")]
     #[test_case("\n# some comment \n\t" => true ; "embedded comment")]
     #[test_case("text" => false ; "no comment")]
+    #[test_case("text # comment\n" => false ; "comment not at start")]
     fn check_ws(to_check: &str) -> bool {
         WS.is_match(to_check)
     }
 
     #[test_case(" \t\n\r" => true ; "valid spaces")]
     #[test_case("# some comment \n" => true ; "comment only")]
+    #[test_case("# comment at EOF" => true ; "comment at EOF")]
     #[test_case("\n# some comment \n\t" => true ; "embedded comment")]
     #[test_case("text\n# some comment \n\ttext" => false ; "full embedded comment")]
     #[test_case("text" => false ; "no comment")]
@@ -316,6 +333,7 @@ mod test {
     #[test_case("\\." => true ; "escape")]
     #[test_case("." => false ; "unescape")]
     #[test_case(" " => false ; "space")]
+    #[test_case("a\\." => false ; "not at start")]
     fn check_plx(to_check: &str) -> bool {
         PLX.is_match(to_check)
     }
@@ -341,6 +359,14 @@ mod test {
         PN_LOCAL_ESC.is_match(to_check)
     }
 
+    #[test_case("local rest" => Ok((" rest", "local")) ; "simple")]
+    #[test_case("loc.al. rest" => Ok((". rest", "loc.al")) ; "trailing dot")]
+    #[test_case("local\\. rest" => Ok((" rest", "local\\.")) ; "escaped trailing dot")]
+    #[test_case("lo:cal rest" => Ok((" rest", "lo:cal")) ; "colon")]
+    fn check_pn_local(i: &str) -> IResult<&str, &str> {
+        pn_local(i)
+    }
+
     #[test_case("0" => true ; "zero")]
     #[test_case("5" => true ; "digit")]
     #[test_case("a" => false ; "hex")]
@@ -1,4 +1,354 @@
 //! Run the W3C test suit for turtle parsers.
 //!
+//! The manifest and the test files are read from `tests/data/turtle`, which
+//! mirrors the layout of <http://www.w3.org/2013/TurtleTests/>. Entries are
+//! run in the order of the manifest and identified by their `mf:name`. Only
+//! part of the suite is vendored so far.
+//!
+//! Only the test types `rdft:TestTurtleEval`,
+//! `rdft:TestTurtlePositiveSyntax` and `rdft:TestTurtleNegativeSyntax` are
+//! executed. All other entries are reported as skipped.
+//!
+//! Eval tests are compared to the expected N-Triples by graph isomorphism,
+//! i.e. blank node labels may differ.
+
+use super::TurtleParser;
+use crate::error::Result;
+use sophia::ns::rdf;
+use sophia::parser::{nt::NTriplesParser, TripleParser};
+use sophia::term::{BoxTerm, Term, TermData};
+use sophia::triple::stream::TripleSource;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+/// Directory of the vendored test suite.
+const SUITE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/turtle");
+/// Base IRI of the test suite's files.
+const SUITE_BASE: &str = "http://www.w3.org/2013/TurtleTests/";
+/// Namespace of the test manifest vocabulary.
+const MF: &str = "http://www.w3.org/2001/sw/DataAccess/tests/test-manifest#";
+/// Namespace of the RDF test vocabulary.
+const RDFT: &str = "http://www.w3.org/ns/rdftest#";
+
+/// Tests that are known to fail.
+///
+/// They are reported but do not fail the test run. Failures of the full suite
+/// belong here, tests must not be removed from the manifest instead.
+const KNOWN_FAILURES: &[&str] = &[];
+
+/// A triple where each term is represented in N-Triples syntax.
+type StrTriple = [String; 3];
+
+/// Kinds of tests that are executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Eval,
+    PositiveSyntax,
+    NegativeSyntax,
+    Unsupported,
+}
+
+/// An entry of the manifest.
+#[derive(Debug)]
+struct Entry {
+    name: String,
+    kind: Kind,
+    action: String,
+    result: Option<String>,
+}
+
+/// Outcome of running an entry.
+#[derive(Debug)]
+enum Outcome {
+    Passed,
+    Failed(String),
+    Skipped,
+}
+
+#[test]
+fn w3c_turtle_suite() {
+    let entries = load_manifest().expect("Manifest of the test suite is readable");
+    let results: Vec<_> = entries.iter().map(|e| (e, run(e))).collect();
+
+    let mut passed = 0;
+    let mut executed = 0;
+    let mut unexpected = vec![];
+
+    println!("{:<50} {:<16} result", "test", "type");
+    for (entry, outcome) in results.iter() {
+        let kind = match entry.kind {
+            Kind::Eval => "eval",
+            Kind::PositiveSyntax => "positive syntax",
+            Kind::NegativeSyntax => "negative syntax",
+            Kind::Unsupported => "unsupported",
+        };
+        let result = match outcome {
+            Outcome::Passed => {
+                executed += 1;
+                passed += 1;
+                "pass".to_string()
+            }
+            Outcome::Failed(reason) => {
+                executed += 1;
+                if !KNOWN_FAILURES.contains(&entry.name.as_str()) {
+                    unexpected.push(entry.name.as_str());
+                }
+                format!("FAIL: {}", reason)
+            }
+            Outcome::Skipped => "skipped".to_string(),
+        };
+        println!("{:<50} {:<16} {}", entry.name, kind, result);
+    }
+
+    println!(
+        "\nConformance: {} of {} executed tests passed ({:.1}%), {} manifest entries",
+        passed,
+        executed,
+        100.0 * passed as f64 / executed.max(1) as f64,
+        entries.len()
+    );
+    assert!(unexpected.is_empty(), "Failed tests: {:?}", unexpected);
+}
+
+/// Runs a single entry of the manifest.
+fn run(entry: &Entry) -> Outcome {
+    if entry.kind == Kind::Unsupported {
+        return Outcome::Skipped;
+    }
+    let doc = match fs::read_to_string(local_path(&entry.action)) {
+        Ok(doc) => doc,
+        Err(e) => return Outcome::Failed(format!("can not read action: {}", e)),
+    };
+
+    match entry.kind {
+        Kind::Unsupported => Outcome::Skipped,
+        Kind::PositiveSyntax => match parse_turtle(&doc, &entry.action) {
+            Ok(_) => Outcome::Passed,
+            Err(e) => Outcome::Failed(e.to_string()),
+        },
+        Kind::NegativeSyntax => match parse_turtle(&doc, &entry.action) {
+            Ok(_) => Outcome::Failed("document was accepted".to_string()),
+            Err(_) => Outcome::Passed,
+        },
+        Kind::Eval => {
+            let parsed = match parse_turtle(&doc, &entry.action) {
+                Ok(parsed) => parsed,
+                Err(e) => return Outcome::Failed(e.to_string()),
+            };
+            let expected = match entry
+                .result
+                .as_ref()
+                .ok_or_else(|| "no result given".to_string())
+                .and_then(|result| fs::read_to_string(local_path(result)).map_err(|e| e.to_string()))
+                .and_then(|nt| parse_ntriples(&nt))
+            {
+                Ok(expected) => expected,
+                Err(e) => return Outcome::Failed(format!("can not read result: {}", e)),
+            };
+
+            if isomorphic(&parsed, &expected) {
+                Outcome::Passed
+            } else {
+                Outcome::Failed(format!(
+                    "graphs differ\n  parsed:   {:?}\n  expected: {:?}",
+                    parsed, expected
+                ))
+            }
+        }
+    }
+}
+
+/// Parse a Turtle document with the given base IRI.
+fn parse_turtle(doc: &str, base: &str) -> Result<Vec<StrTriple>> {
+    TurtleParser {
+        base: Some(base.to_string()),
+        ..TurtleParser::default()
+    }
+    .parse_str(doc)
+    .map(|res| res.map(|t| to_str_triple(&t)))
+    .collect()
+}
+
+/// Parse the expected result with `sophia`'s N-Triples parser.
+fn parse_ntriples(doc: &str) -> std::result::Result<Vec<StrTriple>, String> {
+    let mut g: Vec<[BoxTerm; 3]> = vec![];
+    NTriplesParser {}
+        .parse_str(doc)
+        .in_graph(&mut g)
+        .map_err(|e| e.to_string())?;
+    Ok(g.iter().map(to_str_triple).collect())
+}
+
+fn to_str_triple<TD: TermData>(t: &[Term<TD>; 3]) -> StrTriple {
+    [t[0].to_string(), t[1].to_string(), t[2].to_string()]
+}
+
+/// Maps an IRI of the test suite to the vendored file.
+fn local_path(iri: &str) -> PathBuf {
+    let name = iri.trim_start_matches(SUITE_BASE);
+    PathBuf::from(SUITE_DIR).join(name)
+}
+
+/// Reads the manifest and returns its entries in order of `mf:entries`.
+fn load_manifest() -> Result<Vec<Entry>> {
+    let manifest_iri = format!("{}manifest.ttl", SUITE_BASE);
+    let doc = fs::read_to_string(local_path(&manifest_iri))?;
+    let triples: Vec<[BoxTerm; 3]> = TurtleParser {
+        base: Some(manifest_iri.clone()),
+        ..TurtleParser::default()
+    }
+    .parse_str(&doc)
+    .map(|res| res.map(|[s, p, o]| [s.clone_into(), p.clone_into(), o.clone_into()]))
+    .collect::<Result<_>>()?;
+
+    let mut index = Index::new();
+    for [s, p, o] in triples.iter() {
+        index
+            .entry(s.to_string())
+            .or_default()
+            .entry(p.value().to_string())
+            .or_default()
+            .push(o);
+    }
+    let get = |s: &str, p: &str| first_object(&index, s, p);
+
+    let mf = |name: &str| format!("{}{}", MF, name);
+    let rdft = |name: &str| format!("{}{}", RDFT, name);
+    let first = rdf::iri::first.value().to_string();
+    let rest = rdf::iri::rest.value().to_string();
+    let type_ = rdf::iri::type_.value().to_string();
+    let nil = format!("<{}>", rdf::iri::nil.value());
+
+    let mut entries = vec![];
+    let mut node = get(&format!("<{}>", manifest_iri), &mf("entries"))
+        .map_or_else(|| nil.clone(), |list| list.to_string());
+    while node != nil {
+        let entry = get(&node, &first)
+            .expect("well-formed list of entries")
+            .to_string();
+        let value = |p: &str| get(&entry, p).map(|o| o.value().to_string());
+        let kind = match value(&type_) {
+            Some(t) if t == rdft("TestTurtleEval") => Kind::Eval,
+            Some(t) if t == rdft("TestTurtlePositiveSyntax") => Kind::PositiveSyntax,
+            Some(t) if t == rdft("TestTurtleNegativeSyntax") => Kind::NegativeSyntax,
+            _ => Kind::Unsupported,
+        };
+        entries.push(Entry {
+            name: value(&mf("name")).unwrap_or_else(|| entry.clone()),
+            kind,
+            action: value(&mf("action")).unwrap_or_default(),
+            result: value(&mf("result")),
+        });
+        node = get(&node, &rest)
+            .expect("well-formed list of entries")
+            .to_string();
+    }
+
+    Ok(entries)
+}
+
+/// Objects of the manifest by subject in N-Triples syntax and predicate IRI.
+type Index<'a> = HashMap<String, HashMap<String, Vec<&'a BoxTerm>>>;
+
+/// Returns the first object of subject `s` and predicate `p`.
+fn first_object<'a>(index: &Index<'a>, s: &str, p: &str) -> Option<&'a BoxTerm> {
+    index
+        .get(s)
+        .and_then(|pos| pos.get(p))
+        .and_then(|os| os.first().copied())
+}
+
+/// Checks if both graphs are isomorphic.
+///
+/// As the graphs of the test suite are small, a simple backtracking over the
+/// possible mappings of blank nodes is used.
+fn isomorphic(g1: &[StrTriple], g2: &[StrTriple]) -> bool {
+    let g1: HashSet<&StrTriple> = g1.iter().collect();
+    let g2: HashSet<&StrTriple> = g2.iter().collect();
+    if g1.len() != g2.len() {
+        return false;
+    }
+
+    let blank_nodes = |g: &HashSet<&StrTriple>| -> Vec<String> {
+        let mut bns: Vec<_> = g
+            .iter()
+            .flat_map(|t| t.iter())
+            .filter(|t| is_bnode(t))
+            .cloned()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        bns.sort();
+        bns
+    };
+    let bns1 = blank_nodes(&g1);
+    let bns2 = blank_nodes(&g2);
+    if bns1.len() != bns2.len() {
+        return false;
+    }
+
+    let mut mapping = HashMap::new();
+    find_mapping(&g1, &g2, &bns1, &bns2, &mut mapping)
+}
+
+fn is_bnode(term: &str) -> bool {
+    term.starts_with("_:")
+}
+
+/// Tries to map the remaining blank nodes of `bns1` to unused ones of `bns2`.
+fn find_mapping(
+    g1: &HashSet<&StrTriple>,
+    g2: &HashSet<&StrTriple>,
+    bns1: &[String],
+    bns2: &[String],
+    mapping: &mut HashMap<String, String>,
+) -> bool {
+    // checks all triples whose blank nodes are already mapped
+    let consistent = g1.iter().all(|t| {
+        let mapped: Option<Vec<String>> = t
+            .iter()
+            .map(|term| {
+                if is_bnode(term) {
+                    mapping.get(term).cloned()
+                } else {
+                    Some(term.clone())
+                }
+            })
+            .collect();
+        match mapped {
+            Some(m) => g2.contains(&&[m[0].clone(), m[1].clone(), m[2].clone()]),
+            None => true,
+        }
+    });
+    if !consistent {
+        return false;
+    }
+
+    let next = match bns1.iter().find(|bn| !mapping.contains_key(*bn)) {
+        Some(next) => next,
+        None => return true,
+    };
+    for candidate in bns2.iter() {
+        if mapping.values().any(|v| v == candidate) {
+            continue;
+        }
+        mapping.insert(next.clone(), candidate.clone());
+        if find_mapping(g1, g2, bns1, bns2, mapping) {
+            return true;
+        }
+        mapping.remove(next);
+    }
+
+    false
+}
 
-// TODO
+#[test]
+fn check_isomorphic() {
+    let t = |s: &str, p: &str, o: &str| [s.to_string(), p.to_string(), o.to_string()];
+    let g1 = vec![t("_:a", "<p>", "_:b"), t("_:b", "<p>", "<o>")];
+    let g2 = vec![t("_:y", "<p>", "<o>"), t("_:x", "<p>", "_:y")];
+    let g3 = vec![t("_:x", "<p>", "<o>"), t("_:x", "<p>", "_:y")];
+    assert!(isomorphic(&g1, &g2));
+    assert!(!isomorphic(&g1, &g3));
+}
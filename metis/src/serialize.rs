@@ -1,27 +1,377 @@
 //! Structs and types to serialize RDF.
 
-// pub mod config;
-// pub use self::config::*;
-// pub mod turtle;
-
-// use crate::Format;
-// use sophia::term::TermData;
-// use std::io;
-
-// /// Allow the serialization of `Self`.
-// pub trait Serializable<F: Format> {
-//     /// Error raised at serialization. May differ from `io::Error`
-//     type Error: From<io::Error> + std::error::Error;
-
-//     /// Serialize the current state in the given `Format`.
-//     fn serialize<TD>(
-//         &self,
-//         target: &mut impl io::Write,
-//         conf: &Config<F, TD>,
-//     ) -> Result<(), Self::Error>
-//     where
-//         TD: TermData;
-// }
-
-// #[cfg(test)]
-// mod test {}
+pub mod config;
+pub use self::config::*;
+pub mod n3;
+pub mod turtle;
+
+mod _layout;
+
+use crate::common::Prolog;
+use crate::parse::turtle::terminals::{DECIMAL, DOUBLE, INTEGER, PERCENT, PN_CHARS, PN_CHARS_U};
+use crate::Format;
+use regex::Regex;
+use sophia::ns::xsd;
+use sophia::term::{iri::Iri, literal::Literal, mown_str::MownStr, TermData};
+use std::borrow::Cow;
+use std::io;
+
+/// Characters that may be escaped in a `PN_LOCAL`.
+const PN_LOCAL_ESC: &str = "_~.-!$&'()*+,;=/?#@%";
+
+/// Allow the serialization of `Self`.
+pub trait Serializable<F: Format> {
+    /// Error raised at serialization. May differ from `io::Error`
+    type Error: From<io::Error> + std::error::Error;
+
+    /// Serialize the current state in the given `Format`.
+    fn serialize(&self, target: &mut impl io::Write, conf: &Config<F>) -> Result<(), Self::Error>;
+}
+
+impl<'td, F> Prolog<'td, F>
+where
+    F: Format,
+{
+    /// Write the preamble according to the `prolog` to the target.
+    ///
+    /// The prefixes are written in alphabetical order.
+    pub fn write_preamble<T>(&self, target: &mut T) -> io::Result<()>
+    where
+        T: io::Write,
+    {
+        self.write_preamble_with(target, |_| true)
+    }
+    /// Write the preamble with only the prefixes `keep` returns `true` for.
+    pub(crate) fn write_preamble_with<T, K>(&self, target: &mut T, keep: K) -> io::Result<()>
+    where
+        T: io::Write,
+        K: Fn(&str) -> bool,
+    {
+        let mut prefixes: Vec<_> = self.prefixes.iter().filter(|(p, _)| keep(p)).collect();
+        prefixes.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        for (p, ns) in prefixes.iter() {
+            let ns: Iri<MownStr<'_>> = (*ns).clone().into();
+            write!(target, "@prefix {}: ", p)?;
+            write_iriref(target, &ns.value())?;
+            writeln!(target, " .")?;
+        }
+        if let Some(base) = self.base() {
+            write!(target, "@base ")?;
+            write_iriref(target, &base.value())?;
+            writeln!(target, " .")?;
+        }
+
+        if !prefixes.is_empty() || self.base().is_some() {
+            writeln!(target)?;
+        }
+
+        Ok(())
+    }
+    /// Write an IRI as compact as possible.
+    ///
+    /// The IRI is written as prefixed name if a prefix matches, as relative
+    /// IRI if the base matches and as absolute IRI otherwise.
+    pub fn write_iri<T>(&self, target: &mut T, iri: &str) -> io::Result<()>
+    where
+        T: io::Write,
+    {
+        self.write_iri_with(target, iri, |_| true)
+    }
+    /// Write an IRI with only the prefixes `keep` returns `true` for.
+    pub(crate) fn write_iri_with<T, K>(&self, target: &mut T, iri: &str, keep: K) -> io::Result<()>
+    where
+        T: io::Write,
+        K: Fn(&str) -> bool,
+    {
+        if let Some((p, local)) = self.compact_with(iri, keep) {
+            write!(target, "{}:{}", p, local)
+        } else if let Some(relative) = self.relativize(iri) {
+            write_iriref(target, relative)
+        } else {
+            write_iriref(target, iri)
+        }
+    }
+    /// Write a literal with its language tag or datatype.
+    ///
+    /// Integers, decimals, doubles and booleans are written in their short
+    /// form if the lexical form allows it. The datatype `xsd:string` is
+    /// omitted.
+    pub fn write_literal<T, TD>(&self, target: &mut T, lit: &Literal<TD>) -> io::Result<()>
+    where
+        T: io::Write,
+        TD: TermData,
+    {
+        self.write_literal_with(target, lit, |_| true)
+    }
+    /// Write a literal with only the prefixes `keep` returns `true` for.
+    pub(crate) fn write_literal_with<T, TD, K>(
+        &self,
+        target: &mut T,
+        lit: &Literal<TD>,
+        keep: K,
+    ) -> io::Result<()>
+    where
+        T: io::Write,
+        TD: TermData,
+        K: Fn(&str) -> bool,
+    {
+        let txt = lit.txt();
+        let txt: &str = txt.as_ref();
+
+        if let Some(lang) = lit.lang() {
+            write_string(target, txt)?;
+            return write!(target, "@{}", lang.as_ref());
+        }
+
+        let dt = lit.dt();
+        let dt = dt.value();
+        if is_shorthand(&dt, txt) {
+            target.write_all(txt.as_bytes())
+        } else if *dt == *xsd::iri::string.value() {
+            write_string(target, txt)
+        } else {
+            write_string(target, txt)?;
+            target.write_all(b"^^")?;
+            self.write_iri_with(target, &dt, keep)
+        }
+    }
+    /// Finds the prefix with the longest namespace that allows to write
+    /// `iri` as prefixed name.
+    ///
+    /// Returns the prefix and the escaped local part of the name.
+    pub(crate) fn compact(&self, iri: &str) -> Option<(&str, String)> {
+        self.compact_with(iri, |_| true)
+    }
+    /// Like [`compact()`](#method.compact) with only the prefixes `keep`
+    /// returns `true` for.
+    pub(crate) fn compact_with<K>(&self, iri: &str, keep: K) -> Option<(&str, String)>
+    where
+        K: Fn(&str) -> bool,
+    {
+        let mut best: Option<(&str, usize, String)> = None;
+
+        for (p, ns) in self.prefixes.iter().filter(|(p, _)| keep(p)) {
+            let ns: Iri<MownStr<'_>> = ns.clone().into();
+            let ns = ns.value();
+            if !iri.starts_with(&*ns) {
+                continue;
+            }
+            let local = match escape_local(&iri[ns.len()..]) {
+                Some(local) => local,
+                None => continue,
+            };
+            let better = match &best {
+                None => true,
+                Some((best_p, len, _)) => {
+                    ns.len() > *len || (ns.len() == *len && p.as_ref() < *best_p)
+                }
+            };
+            if better {
+                best = Some((p.as_ref(), ns.len(), local));
+            }
+        }
+
+        best.map(|(p, _, local)| (p, local))
+    }
+    /// Returns `iri` relative to the base if resolving the result against
+    /// the base gives `iri` again.
+    fn relativize<'i>(&self, iri: &'i str) -> Option<&'i str> {
+        let base = self.base()?;
+        let base = base.value();
+        if base.contains('#') || !iri.starts_with(&*base) {
+            return None;
+        }
+
+        let rest = &iri[base.len()..];
+        if rest.is_empty() || rest.starts_with('#') {
+            return Some(rest);
+        }
+        if !base.ends_with('/') || rest.starts_with('/') || rest.starts_with('?') {
+            return None;
+        }
+        let path = rest.split(|c| c == '?' || c == '#').next().unwrap_or("");
+        let first_segment = path.split('/').next().unwrap_or("");
+        if first_segment.contains(':') || path.split('/').any(|seg| seg == "." || seg == "..") {
+            return None;
+        }
+
+        Some(rest)
+    }
+}
+
+/// Checks if a literal can be written without quotes and datatype.
+fn is_shorthand(dt: &str, txt: &str) -> bool {
+    let full_match = |re: &Regex| re.find(txt).map_or(false, |m| m.end() == txt.len());
+
+    if dt == &*xsd::iri::integer.value() {
+        full_match(&INTEGER)
+    } else if dt == &*xsd::iri::decimal.value() {
+        full_match(&DECIMAL)
+    } else if dt == &*xsd::iri::double.value() {
+        full_match(&DOUBLE)
+    } else if dt == &*xsd::iri::boolean.value() {
+        txt == "true" || txt == "false"
+    } else {
+        false
+    }
+}
+
+/// Writes `iri` enclosed in `<` and `>`.
+///
+/// Characters that are not allowed in an `IRIREF` are written as `\uXXXX`.
+fn write_iriref(target: &mut impl io::Write, iri: &str) -> io::Result<()> {
+    let mut last = 0;
+
+    target.write_all(b"<")?;
+    for (idx, c) in iri.char_indices() {
+        if c <= ' ' || "<>\"{}|^`\\".contains(c) {
+            target.write_all(iri[last..idx].as_bytes())?;
+            write!(target, "\\u{:04X}", c as u32)?;
+            last = idx + c.len_utf8();
+        }
+    }
+    target.write_all(iri[last..].as_bytes())?;
+    target.write_all(b">")
+}
+
+/// Writes `txt` as quoted string.
+///
+/// Strings with line breaks are written as long strings
+/// (`"""..."""`). In long strings, line breaks and quotes that can not
+/// be confused with the end of the string are not escaped.
+fn write_string(target: &mut impl io::Write, txt: &str) -> io::Result<()> {
+    let long = txt.contains('\n');
+    let quotes: &[u8] = if long { b"\"\"\"" } else { b"\"" };
+    let mut last = 0;
+
+    target.write_all(quotes)?;
+    let mut chars = txt.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        let escaped: Cow<str> = match c {
+            '\n' if long => continue,
+            '"' if long && !matches!(chars.peek(), Some((_, '"')) | None) => continue,
+            '"' => "\\\"".into(),
+            '\\' => "\\\\".into(),
+            '\n' => "\\n".into(),
+            '\r' => "\\r".into(),
+            '\t' => "\\t".into(),
+            '\u{8}' => "\\b".into(),
+            '\u{c}' => "\\f".into(),
+            c if c.is_control() => format!("\\u{:04X}", c as u32).into(),
+            _ => continue,
+        };
+        target.write_all(txt[last..idx].as_bytes())?;
+        target.write_all(escaped.as_bytes())?;
+        last = idx + c.len_utf8();
+    }
+    target.write_all(txt[last..].as_bytes())?;
+    target.write_all(quotes)
+}
+
+/// Escapes `local` to be used as `PN_LOCAL`.
+///
+/// Returns `None` if `local` contains characters that are neither allowed
+/// nor can be escaped.
+fn escape_local(local: &str) -> Option<String> {
+    let mut escaped = String::with_capacity(local.len());
+    let mut buf = [0; 4];
+
+    for (idx, c) in local.char_indices() {
+        let first = idx == 0;
+        let last = idx + c.len_utf8() == local.len();
+        let as_str = c.encode_utf8(&mut buf);
+
+        if c == '%' && PERCENT.is_match(&local[idx..]) {
+            escaped.push(c);
+        } else if c == ':' || c.is_ascii_digit() || PN_CHARS_U.is_match(as_str) {
+            escaped.push(c);
+        } else if !first && ((c == '.' && !last) || PN_CHARS.is_match(as_str)) {
+            escaped.push(c);
+        } else if PN_LOCAL_ESC.contains(c) {
+            escaped.push('\\');
+            escaped.push(c);
+        } else {
+            return None;
+        }
+    }
+
+    Some(escaped)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse::turtle::terminals::pn_local;
+    use crate::Turtle;
+    use test_case::test_case;
+
+    #[test]
+    fn check_write_preamble() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let mut prolog = Prolog::<Turtle>::default();
+        let mut empty = vec![];
+        prolog.write_preamble(&mut empty)?;
+        assert!(empty.is_empty());
+
+        prolog
+            .add_prefix("xsd", "http://www.w3.org/2001/XMLSchema#".into())?
+            .add_prefix("ex", "http://example.org/".into())?
+            .set_base(Iri::new("http://example.org/base/".into())?)?;
+        let mut preamble = vec![];
+        prolog.write_preamble(&mut preamble)?;
+        assert_eq!(
+            String::from_utf8(preamble)?,
+            "@prefix ex: <http://example.org/> .\n\
+             @prefix xsd: <http://www.w3.org/2001/XMLSchema#> .\n\
+             @base <http://example.org/base/> .\n\n"
+        );
+        Ok(())
+    }
+
+    #[test_case("abc" => Some("abc".to_string()) ; "plain")]
+    #[test_case("" => Some("".to_string()) ; "empty")]
+    #[test_case("a.b" => Some("a.b".to_string()) ; "inner dot")]
+    #[test_case("a." => Some("a\\.".to_string()) ; "trailing dot")]
+    #[test_case("-a" => Some("\\-a".to_string()) ; "leading hyphen")]
+    #[test_case("1a:b" => Some("1a:b".to_string()) ; "digit and colon")]
+    #[test_case("a/b?c=d#e" => Some("a\\/b\\?c\\=d\\#e".to_string()) ; "escaped")]
+    #[test_case("a%20b%zz" => Some("a%20b\\%zz".to_string()) ; "percent")]
+    #[test_case("a b" => None ; "space")]
+    #[test_case("a<b" => None ; "angle bracket")]
+    fn check_escape_local(local: &str) -> Option<String> {
+        let escaped = escape_local(local);
+        if let Some(escaped) = &escaped {
+            if !escaped.is_empty() {
+                assert_eq!(pn_local(escaped), Ok(("", escaped.as_str())));
+            }
+        }
+        escaped
+    }
+
+    #[test_case("http://example.org/a" => "ex:a" ; "prefixed")]
+    #[test_case("http://example.org/ns#a" => "ns:a" ; "longest namespace")]
+    #[test_case("http://example.org/" => "ex:" ; "namespace only")]
+    #[test_case("http://example.org/a/b" => "ex:a\\/b" ; "escaped local")]
+    #[test_case("http://example.org/a b" => "<http://example.org/a\\u0020b>" ; "invalid local")]
+    #[test_case("http://example.com/base/{a|b}" => "<\\u007Ba\\u007Cb\\u007D>" ; "escaped relative")]
+    #[test_case("http://example.com/<\"^`\\>\n" => "<http://example.com/\\u003C\\u0022\\u005E\\u0060\\u005C\\u003E\\u000A>" ; "escaped absolute")]
+    #[test_case("http://example.com/base/a/b" => "<a/b>" ; "relative")]
+    #[test_case("http://example.com/base/" => "<>" ; "base")]
+    #[test_case("http://example.com/base/#f" => "<#f>" ; "fragment")]
+    #[test_case("http://example.com/base/a:b" => "<http://example.com/base/a:b>" ; "scheme like")]
+    #[test_case("http://example.com/base/../a" => "<http://example.com/base/../a>" ; "dot segment")]
+    #[test_case("http://example.com/other" => "<http://example.com/other>" ; "absolute")]
+    fn check_write_iri(iri: &str) -> String {
+        let mut prolog = Prolog::<Turtle>::default();
+        prolog
+            .add_prefix("ex", "http://example.org/".into())
+            .unwrap()
+            .add_prefix("ns", "http://example.org/ns#".into())
+            .unwrap()
+            .set_base(Iri::new("http://example.com/base/".into()).unwrap())
+            .unwrap();
+        let mut written = vec![];
+        prolog.write_iri(&mut written, iri).unwrap();
+        String::from_utf8(written).unwrap()
+    }
+}
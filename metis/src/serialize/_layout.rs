@@ -0,0 +1,138 @@
+//! Helpers to lay out serialized documents within a line width.
+//!
+//! Serializers write to a `ColumnWriter` to know the current column. To
+//! check if something fits on the current line it is first written to a
+//! `FlatWriter` which fails as soon as the line is full or broken.
+
+use std::io;
+
+/// Writer that keeps track of the column of the current line.
+pub(crate) struct ColumnWriter<W> {
+    inner: W,
+    column: usize,
+}
+
+impl<W> ColumnWriter<W> {
+    /// Wrap `inner` and start at column 0.
+    pub(crate) fn new(inner: W) -> Self {
+        Self { inner, column: 0 }
+    }
+    /// The column the next character is written to.
+    pub(crate) fn column(&self) -> usize {
+        self.column
+    }
+    /// Get the wrapped writer back.
+    pub(crate) fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W> ColumnWriter<W>
+where
+    W: io::Write,
+{
+    /// Start a new line and continue at `column`.
+    pub(crate) fn align(&mut self, column: usize) -> io::Result<()> {
+        io::Write::write_all(self, b"\n")?;
+        for _ in 0..column {
+            io::Write::write_all(self, b" ")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<W> io::Write for ColumnWriter<W>
+where
+    W: io::Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        for b in buf[..written].iter() {
+            if *b == b'\n' {
+                self.column = 0;
+            } else if is_char_start(*b) {
+                self.column += 1;
+            }
+        }
+
+        Ok(written)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Writer that accepts only a single line of limited width.
+pub(crate) struct FlatWriter {
+    buf: Vec<u8>,
+    left: usize,
+}
+
+impl FlatWriter {
+    /// Accept at most `width` characters.
+    pub(crate) fn new(width: usize) -> Self {
+        Self {
+            buf: vec![],
+            left: width,
+        }
+    }
+    /// Get the written line.
+    pub(crate) fn into_inner(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+impl io::Write for FlatWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for b in buf.iter() {
+            if *b == b'\n' {
+                return Err(io::Error::new(io::ErrorKind::Other, "line is broken"));
+            } else if is_char_start(*b) {
+                if self.left == 0 {
+                    return Err(io::Error::new(io::ErrorKind::Other, "line is full"));
+                }
+                self.left -= 1;
+            }
+        }
+        self.buf.extend_from_slice(buf);
+
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Checks if `b` is the first byte of an UTF-8 encoded character.
+fn is_char_start(b: u8) -> bool {
+    b & 0xC0 != 0x80
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn check_column() -> io::Result<()> {
+        let mut w = ColumnWriter::new(vec![]);
+        w.write_all("ab ä".as_bytes())?;
+        assert_eq!(w.column(), 4);
+        w.align(2)?;
+        w.write_all(b"c")?;
+        assert_eq!(w.column(), 3);
+        assert_eq!(w.into_inner(), "ab ä\n  c".as_bytes());
+        Ok(())
+    }
+
+    #[test]
+    fn check_flat() {
+        let mut w = FlatWriter::new(4);
+        assert!(w.write_all("abä".as_bytes()).is_ok());
+        assert!(w.write_all(b"\n").is_err());
+        assert!(w.write_all(b"c").is_ok());
+        assert!(w.write_all(b"d").is_err());
+        assert_eq!(w.into_inner(), "abäc".as_bytes());
+    }
+}
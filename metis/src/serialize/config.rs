@@ -1,96 +1,105 @@
 //! This module provides the configuration of serialization the supported
 //! formats.
 
+mod _discovery;
 mod _indentation;
 pub use self::_indentation::*;
 
 use crate::common::Prolog;
 use crate::error::{Error, Result};
 use crate::Format;
-use sophia::term::TermData;
+use sophia::ns::rdf;
+use std::collections::HashSet;
 use std::io;
 
 /// Options to serialize format `F`.
 #[derive(Clone, Debug)]
-pub struct Config<F, TD>
+pub struct Config<F>
 where
     F: Format,
-    TD: TermData,
 {
-    pub(crate) prolog: Prolog<F, TD>,
+    pub(crate) prolog: Prolog<'static, F>,
+    /// Prefixes that were not used by the graph of the last discovery.
+    pub(crate) unused_prefixes: HashSet<String>,
     pub(crate) indent: Indentation,
     pub(crate) space: Indentation,
+    pub(crate) line_width: Option<usize>,
+    pub(crate) predicate_order: Vec<String>,
+    pub(crate) type_as_a: bool,
+    pub(crate) canonical: bool,
     pub(crate) format: F::ConfigData,
 }
 
-impl<F, TD> Default for Config<F, TD>
+impl<F> Default for Config<F>
 where
     F: Format,
     F::ConfigData: Default,
-    TD: TermData,
 {
     fn default() -> Self {
         Self {
             prolog: Prolog::default(),
+            unused_prefixes: HashSet::new(),
             indent: Indentation::default(),
             space: Indentation::space(),
+            line_width: None,
+            predicate_order: vec![[rdf::PREFIX, "type"].concat()],
+            type_as_a: true,
+            canonical: false,
             format: F::ConfigData::default(),
         }
     }
 }
 
-impl<F, TD> std::ops::Deref for Config<F, TD>
+impl<F> std::ops::Deref for Config<F>
 where
     F: Format,
-    TD: TermData,
 {
-    type Target = Prolog<F, TD>;
+    type Target = Prolog<'static, F>;
 
     fn deref(&self) -> &Self::Target {
         &self.prolog
     }
 }
 
-impl<F, TD> std::ops::DerefMut for Config<F, TD>
+impl<F> std::ops::DerefMut for Config<F>
 where
     F: Format,
-    TD: TermData,
 {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.prolog
     }
 }
 
-impl<F, TD> Config<F, TD>
+impl<F> Config<F>
 where
     F: Format,
-    TD: TermData,
     Self: Default,
 {
     /// Create the default configuration with the default prefixes.
     ///
     /// Uses internally [`add_default_prefixes()`](#method.add_default_prefixes)
-    pub fn with_default_prefixes() -> Self
-    where
-        TD: From<&'static str>,
-    {
+    pub fn with_default_prefixes() -> Self {
         let mut tsc = Self::default();
         tsc.add_default_prefixes();
         tsc
     }
 }
 
-impl<F, TD> Config<F, TD>
+impl<F> Config<F>
 where
     F: Format,
-    TD: TermData,
 {
     /// A default config with the given `Format`.
     pub fn new(format: F::ConfigData) -> Self {
         Self {
             prolog: Prolog::default(),
+            unused_prefixes: HashSet::new(),
             indent: Indentation::default(),
             space: Indentation::space(),
+            line_width: None,
+            predicate_order: vec![[rdf::PREFIX, "type"].concat()],
+            type_as_a: true,
+            canonical: false,
             format,
         }
     }
@@ -122,4 +131,59 @@ where
     pub fn write_space(&self, target: &mut impl io::Write) -> io::Result<()> {
         self.space.serialize(target)
     }
+    /// Set the maximum width of lines.
+    ///
+    /// Serializers that support it keep short object lists, collections and
+    /// nested blocks on one line and break them if they are too long. `None`,
+    /// the default, disables this.
+    pub fn set_line_width(&mut self, width: Option<usize>) -> &mut Self {
+        self.line_width = width;
+        self
+    }
+    /// The maximum width of lines, if set.
+    pub fn line_width(&self) -> Option<usize> {
+        self.line_width
+    }
+    /// Set the predicates that are written first for each subject.
+    ///
+    /// The predicates are given as IRIs and written in the given order. All
+    /// other predicates follow in alphabetical order. By default only
+    /// `rdf:type` is written first.
+    pub fn set_predicate_order<I, S>(&mut self, predicates: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.predicate_order = predicates.into_iter().map(Into::into).collect();
+        self
+    }
+    /// The IRIs of the predicates that are written first.
+    pub fn predicate_order(&self) -> &[String] {
+        &self.predicate_order
+    }
+    /// Set if `rdf:type` in predicate position is written as `a`.
+    ///
+    /// This is the default.
+    pub fn set_type_as_a(&mut self, type_as_a: bool) -> &mut Self {
+        self.type_as_a = type_as_a;
+        self
+    }
+    /// Checks if `rdf:type` in predicate position is written as `a`.
+    pub fn is_type_as_a(&self) -> bool {
+        self.type_as_a
+    }
+    /// Request canonical output.
+    ///
+    /// Serializers that collect the whole graph then write everything in a
+    /// defined order and replace blank node labels by labels derived from the
+    /// graph's structure. Serializing isomorphic graphs gives identical
+    /// output.
+    pub fn set_canonical(&mut self, canonical: bool) -> &mut Self {
+        self.canonical = canonical;
+        self
+    }
+    /// Checks if canonical output is requested.
+    pub fn is_canonical(&self) -> bool {
+        self.canonical
+    }
 }
@@ -0,0 +1,236 @@
+//! Discovery of prefixes for the namespaces a graph uses.
+
+use super::Config;
+use crate::ns::VOCABULARIES;
+use crate::serialize::{escape_local, is_shorthand};
+use crate::Format;
+use sophia::ns::xsd;
+use sophia::term::{iri::Iri, literal::Literal, mown_str::MownStr, ns::Namespace, Term, TermData};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::io;
+
+impl<F> Config<F>
+where
+    F: Format,
+{
+    /// Add prefixes for the namespaces frequently used by `terms`.
+    ///
+    /// The namespace of an IRI is everything up to the last `#` or `/`.
+    /// Namespaces that are used at least `min_uses` times and have no prefix
+    /// yet get the prefix of their vocabulary in
+    /// [`VOCABULARIES`](../ns/constant.VOCABULARIES.html) or a generated
+    /// prefix `ns1`, `ns2`, ...
+    ///
+    /// Afterwards, [`write_preamble()`](#method.write_preamble) leaves out
+    /// all prefixes that are not used by `terms` and
+    /// [`write_iri()`](#method.write_iri) does not use them to compact IRIs.
+    /// This holds until the next discovery, even if a left out prefix is
+    /// added again.
+    pub fn discover_prefixes<'t, I, TD>(&mut self, terms: I, min_uses: usize) -> &mut Self
+    where
+        I: IntoIterator<Item = &'t Term<TD>>,
+        TD: TermData + 't,
+    {
+        let iris: Vec<String> = terms.into_iter().filter_map(written_iri).collect();
+
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for ns in iris.iter().filter_map(|iri| namespace(iri)) {
+            *counts.entry(ns).or_insert(0) += 1;
+        }
+        let mut frequent: Vec<(&str, usize)> = counts
+            .into_iter()
+            .filter(|(_, count)| *count >= min_uses)
+            .collect();
+        // most used first, so they get the lower generated numbers
+        frequent.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+
+        for (ns, _) in frequent {
+            if self.has_namespace(ns) {
+                continue;
+            }
+            let prefix = self.free_prefix(ns);
+            if let Ok(ns) = Namespace::new(MownStr::from(ns.to_string())) {
+                self.prolog.prefixes.insert(Cow::Owned(prefix), ns);
+            }
+        }
+
+        let unused = {
+            let used: HashSet<&str> = iris
+                .iter()
+                .filter_map(|iri| self.prolog.compact(iri).map(|(p, _)| p))
+                .collect();
+            self.prefixes
+                .keys()
+                .filter(|p| !used.contains(&***p))
+                .map(|p| p.to_string())
+                .collect()
+        };
+        self.unused_prefixes = unused;
+        self
+    }
+    /// Write the preamble according to the prolog to the target.
+    ///
+    /// The prefixes are written in alphabetical order. Prefixes that were not
+    /// used by the terms of the last
+    /// [`discover_prefixes()`](#method.discover_prefixes) are left out.
+    pub fn write_preamble<T>(&self, target: &mut T) -> io::Result<()>
+    where
+        T: io::Write,
+    {
+        self.prolog
+            .write_preamble_with(target, |p| self.is_declared(p))
+    }
+    /// Write an IRI as compact as possible.
+    ///
+    /// Only the prefixes written by
+    /// [`write_preamble()`](#method.write_preamble) are used.
+    pub fn write_iri<T>(&self, target: &mut T, iri: &str) -> io::Result<()>
+    where
+        T: io::Write,
+    {
+        self.prolog
+            .write_iri_with(target, iri, |p| self.is_declared(p))
+    }
+    /// Write a literal with its language tag or datatype.
+    ///
+    /// Only the prefixes written by
+    /// [`write_preamble()`](#method.write_preamble) are used.
+    pub fn write_literal<T, TD>(&self, target: &mut T, lit: &Literal<TD>) -> io::Result<()>
+    where
+        T: io::Write,
+        TD: TermData,
+    {
+        self.prolog
+            .write_literal_with(target, lit, |p| self.is_declared(p))
+    }
+    /// Checks if the prefix is written to the preamble.
+    fn is_declared(&self, prefix: &str) -> bool {
+        !self.unused_prefixes.contains(prefix)
+    }
+    /// Checks if a prefix is declared for `ns`.
+    fn has_namespace(&self, ns: &str) -> bool {
+        self.prefixes.values().any(|declared| {
+            let declared: Iri<MownStr<'_>> = declared.clone().into();
+            *declared.value() == *ns
+        })
+    }
+    /// Returns the prefix of the vocabulary `ns` or a generated prefix that
+    /// is not declared yet.
+    fn free_prefix(&self, ns: &str) -> String {
+        VOCABULARIES
+            .iter()
+            .filter(|(_, vocab)| *vocab == ns)
+            .map(|(p, _)| p.to_string())
+            .chain((1..).map(|n| format!("ns{}", n)))
+            .find(|p| !self.prefixes.contains_key(p.as_str()))
+            .expect("There are infinitely many generated prefixes")
+    }
+}
+
+/// Returns the IRI that is written for `t`, if any.
+///
+/// Besides IRIs this is the datatype of literals that are not written in
+/// their short form.
+fn written_iri<TD>(t: &Term<TD>) -> Option<String>
+where
+    TD: TermData,
+{
+    match t {
+        Term::Iri(iri) => Some(iri.value().to_string()),
+        Term::Literal(lit) if lit.lang().is_none() => {
+            let txt = lit.txt();
+            let dt = lit.dt();
+            let dt = dt.value();
+            if is_shorthand(&dt, txt.as_ref()) || *dt == *xsd::iri::string.value() {
+                None
+            } else {
+                Some(dt.to_string())
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns the namespace of `iri` if the rest is a valid local name.
+fn namespace(iri: &str) -> Option<&str> {
+    let pos = iri.rfind(|c| c == '#' || c == '/')?;
+    let (ns, local) = iri.split_at(pos + 1);
+    escape_local(local).map(|_| ns)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::Result;
+    use crate::parse::turtle::Parser;
+    use crate::Turtle;
+    use test_case::test_case;
+
+    const DOC: &str = r#"
+        <http://example.org/people/alice> <http://xmlns.com/foaf/0.1/name> "Alice" ;
+            <http://xmlns.com/foaf/0.1/knows> <http://example.org/people/bob> .
+        <http://example.org/people/bob> <http://xmlns.com/foaf/0.1/name> "Bob" ;
+            <http://www.w3.org/2000/01/rdf-schema#seeAlso> <http://example.org/other/bob> .
+    "#;
+
+    fn preamble(config: &Config<Turtle>) -> String {
+        let mut preamble = vec![];
+        config.write_preamble(&mut preamble).unwrap();
+        String::from_utf8(preamble).unwrap()
+    }
+
+    #[test]
+    fn check_discover_prefixes() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let triples = Parser::new(DOC).collect::<Result<Vec<_>>>()?;
+        let mut config = Config::<Turtle>::with_default_prefixes();
+        config.discover_prefixes(triples.iter().flat_map(|t| t.iter()), 2);
+
+        assert_eq!(
+            preamble(&config),
+            "@prefix foaf: <http://xmlns.com/foaf/0.1/> .\n\
+             @prefix ns1: <http://example.org/people/> .\n\
+             @prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .\n\n"
+        );
+
+        // running again changes nothing
+        config.discover_prefixes(triples.iter().flat_map(|t| t.iter()), 2);
+        assert_eq!(config.prefixes.len(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn check_undiscovered_iri() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let triples = Parser::new(DOC).collect::<Result<Vec<_>>>()?;
+        let mut config = Config::<Turtle>::with_default_prefixes();
+        config.discover_prefixes(triples.iter().flat_map(|t| t.iter()), 2);
+
+        let mut written = vec![];
+        config.write_iri(&mut written, "http://xmlns.com/foaf/0.1/mbox")?;
+        written.push(b' ');
+        config.write_iri(&mut written, "http://www.w3.org/2001/XMLSchema#date")?;
+        written.push(b' ');
+        config.write_literal(&mut written, &Literal::new_dt("2020", xsd::iri::gYear))?;
+        assert_eq!(
+            String::from_utf8(written)?,
+            "foaf:mbox <http://www.w3.org/2001/XMLSchema#date> \
+             \"2020\"^^<http://www.w3.org/2001/XMLSchema#gYear>"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn check_nothing_used() {
+        let mut config = Config::<Turtle>::with_default_prefixes();
+        config.discover_prefixes(Vec::<&Term<&str>>::new(), 1);
+        assert_eq!(preamble(&config), "");
+    }
+
+    #[test_case("http://xmlns.com/foaf/0.1/name", Some("http://xmlns.com/foaf/0.1/") ; "slash")]
+    #[test_case("http://www.w3.org/2002/07/owl#Class", Some("http://www.w3.org/2002/07/owl#") ; "hash")]
+    #[test_case("http://example.org/a#b/", Some("http://example.org/a#b/") ; "empty local")]
+    #[test_case("http://example.org/a b", None ; "invalid local")]
+    fn check_namespace(iri: &str, ns: Option<&str>) {
+        assert_eq!(namespace(iri), ns);
+    }
+}
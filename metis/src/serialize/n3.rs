@@ -0,0 +1,571 @@
+//! Serialize the Notation3 format.
+//!
+//! Each statement is written on its own line. Formulas are written as
+//! `{ ... }` blocks whose statements are indented by one more level.
+//!
+//! If the `Config` sets a line width, formulas and lists that fit are written
+//! on one line, e.g. `{ ?x a :C }`. Lists that are too long are written one
+//! item per line aligned under the first item.
+//!
+//! Terms are written as follows:
+//!
+//! - IRIs as prefixed names or relative to the base of the `Config` if
+//!   possible, otherwise as `<...>`. `log:implies` in predicate position is
+//!   written as `=>`.
+//! - Literals in their short form if possible, e.g. `1` or `true`.
+//! - Universals as `?x`.
+//! - Existentials as `[]` if they occur only once, otherwise as `_:b`.
+//! - Lists as `( ... )`, i.e. existentials that only serve as nodes of a
+//!   `rdf:first`/`rdf:rest` chain are not written as triples.
+
+use crate::n3::{N3Term, N3};
+use crate::ns::log;
+use crate::serialize::_layout::{ColumnWriter, FlatWriter};
+use crate::serialize::Config;
+use sophia::ns::rdf;
+use sophia::term::{iri::Iri, literal::Literal, TermData};
+use std::collections::{HashMap, HashSet};
+use std::io;
+
+/// Number of occurrences of existentials.
+type Uses<'t, TD> = HashMap<&'t N3Term<TD>, usize>;
+
+/// Serializer for N3 statements.
+pub struct Serializer<'a, W>
+where
+    W: io::Write,
+{
+    target: ColumnWriter<W>,
+    config: &'a Config<N3>,
+    /// Write everything on one line.
+    flat: bool,
+}
+
+impl<'a, W> Serializer<'a, W>
+where
+    W: io::Write,
+{
+    /// Create a new serializer for the given target with the given
+    /// configuration.
+    ///
+    /// The preamble for `config` is written immediately.
+    pub fn new(target: W, config: &'a Config<N3>) -> io::Result<Self> {
+        let mut target = ColumnWriter::new(target);
+        config.write_preamble(&mut target)?;
+
+        Ok(Self {
+            target,
+            config,
+            flat: false,
+        })
+    }
+    /// Serialize the statements of a document.
+    pub fn serialize<TD>(&mut self, statements: &[[N3Term<TD>; 3]]) -> io::Result<()>
+    where
+        TD: TermData,
+    {
+        let mut uses = Uses::new();
+        count_existentials(statements, &mut uses);
+        self.write_statements(statements, &uses, 0)
+    }
+    /// Drop the serializer and get the target back.
+    pub fn finish(self) -> W {
+        self.target.into_inner()
+    }
+    /// Write the indention for the given level.
+    fn indent(&mut self, level: usize) -> io::Result<()> {
+        for _ in 0..level {
+            self.config.write_indent(&mut self.target)?;
+        }
+
+        Ok(())
+    }
+    /// Write the statements of a formula, each on its own line.
+    ///
+    /// If everything is written on one line, the statements are only
+    /// separated by ` . `.
+    fn write_statements<'t, TD>(
+        &mut self,
+        statements: &'t [[N3Term<TD>; 3]],
+        uses: &Uses<'t, TD>,
+        level: usize,
+    ) -> io::Result<()>
+    where
+        TD: TermData,
+    {
+        let lists = Lists::new(statements, uses);
+
+        let mut first = true;
+        for (idx, [s, p, o]) in statements.iter().enumerate() {
+            if lists.consumed.contains(&idx) {
+                continue;
+            }
+
+            if !self.flat {
+                self.indent(level)?;
+            } else if !first {
+                self.target.write_all(b" .")?;
+                self.config.write_space(&mut self.target)?;
+            }
+            first = false;
+            self.write_term(s, &lists, uses, level)?;
+            self.config.write_space(&mut self.target)?;
+            if is_iri(p, &log::iri::implies) {
+                self.target.write_all(b"=>")?;
+            } else {
+                self.write_term(p, &lists, uses, level)?;
+            }
+            self.config.write_space(&mut self.target)?;
+            self.write_term(o, &lists, uses, level)?;
+            if !self.flat {
+                self.target.write_all(b" .\n")?;
+            }
+        }
+
+        Ok(())
+    }
+    /// Write a single term.
+    fn write_term<'t, TD>(
+        &mut self,
+        t: &'t N3Term<TD>,
+        lists: &Lists<'t, TD>,
+        uses: &Uses<'t, TD>,
+        level: usize,
+    ) -> io::Result<()>
+    where
+        TD: TermData,
+    {
+        match t {
+            N3Term::Iri(_) if is_iri(t, &rdf::iri::nil) => self.target.write_all(b"()"),
+            N3Term::Iri(iri) => self.write_iri(&iri.value()),
+            N3Term::Literal(lit) => self.write_literal(lit),
+            N3Term::Universal(var) => write!(self.target, "?{}", var.value()),
+            N3Term::Existential(bn) => {
+                if let Some(items) = lists.items.get(t) {
+                    self.write_list(items, lists, uses, level)
+                } else if uses.get(t) == Some(&1) {
+                    self.target.write_all(b"[]")
+                } else {
+                    write!(self.target, "_:{}", bn.value())
+                }
+            }
+            N3Term::Formula(f) => self.write_formula(f, uses, level),
+        }
+    }
+    /// Write the items of a list as `( ... )`.
+    ///
+    /// If the list does not fit on the line, each item is written on a line
+    /// of its own aligned under the first.
+    fn write_list<'t, TD>(
+        &mut self,
+        items: &[&'t N3Term<TD>],
+        lists: &Lists<'t, TD>,
+        uses: &Uses<'t, TD>,
+        level: usize,
+    ) -> io::Result<()>
+    where
+        TD: TermData,
+    {
+        if self.try_flat(|ser| ser.write_list(items, lists, uses, level))? {
+            return Ok(());
+        }
+        let wrap = self.config.line_width().is_some() && !self.flat;
+
+        self.target.write_all(b"(")?;
+        self.config.write_space(&mut self.target)?;
+        let column = self.target.column();
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                if wrap {
+                    self.target.align(column)?;
+                } else {
+                    self.config.write_space(&mut self.target)?;
+                }
+            }
+            self.write_term(item, lists, uses, level)?;
+        }
+        self.config.write_space(&mut self.target)?;
+        self.target.write_all(b")")
+    }
+    /// Write a formula as `{ ... }` block.
+    fn write_formula<'t, TD>(
+        &mut self,
+        statements: &'t [[N3Term<TD>; 3]],
+        uses: &Uses<'t, TD>,
+        level: usize,
+    ) -> io::Result<()>
+    where
+        TD: TermData,
+    {
+        if statements.is_empty() {
+            return self.target.write_all(b"{}");
+        }
+        if self.try_flat(|ser| ser.write_formula(statements, uses, level))? {
+            return Ok(());
+        }
+
+        if self.flat {
+            self.target.write_all(b"{")?;
+            self.config.write_space(&mut self.target)?;
+            self.write_statements(statements, uses, level + 1)?;
+            self.config.write_space(&mut self.target)?;
+            return self.target.write_all(b"}");
+        }
+        self.target.write_all(b"{\n")?;
+        self.write_statements(statements, uses, level + 1)?;
+        self.indent(level)?;
+        self.target.write_all(b"}")
+    }
+    /// Try to write something on the rest of the current line.
+    ///
+    /// `write` is called with a serializer that writes everything on one
+    /// line. If that fits into the line width, the line is written and `true`
+    /// is returned. Otherwise, nothing is written. Without a line width
+    /// nothing is tried.
+    fn try_flat<F>(&mut self, write: F) -> io::Result<bool>
+    where
+        F: FnOnce(&mut Serializer<'a, FlatWriter>) -> io::Result<()>,
+    {
+        let width = match self.config.line_width() {
+            Some(width) if !self.flat => width,
+            _ => return Ok(false),
+        };
+        // leave room for a following ` .`
+        let left = width.saturating_sub(self.target.column() + 2);
+
+        let mut flat = Serializer {
+            target: ColumnWriter::new(FlatWriter::new(left)),
+            config: self.config,
+            flat: true,
+        };
+        if write(&mut flat).is_ok() {
+            self.target
+                .write_all(&flat.target.into_inner().into_inner())?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+    /// Write an IRI, as prefixed name if possible.
+    fn write_iri(&mut self, iri: &str) -> io::Result<()> {
+        self.config.write_iri(&mut self.target, iri)
+    }
+    /// Write a literal, in its short form if possible.
+    fn write_literal<TD>(&mut self, lit: &Literal<TD>) -> io::Result<()>
+    where
+        TD: TermData,
+    {
+        self.config.write_literal(&mut self.target, lit)
+    }
+}
+
+/// The lists of a formula that are written as `( ... )`.
+struct Lists<'t, TD>
+where
+    TD: TermData,
+{
+    /// The items of each list by the list's first node.
+    items: HashMap<&'t N3Term<TD>, Vec<&'t N3Term<TD>>>,
+    /// Indices of the `rdf:first` and `rdf:rest` statements that are written
+    /// as part of a list.
+    consumed: HashSet<usize>,
+}
+
+impl<'t, TD> Lists<'t, TD>
+where
+    TD: TermData,
+{
+    /// Finds the lists of a formula.
+    ///
+    /// A node of a list is an existential with exactly one `rdf:first` and
+    /// one `rdf:rest` statement that is referenced only once more. All three
+    /// occurrences must be within the formula.
+    fn new(statements: &'t [[N3Term<TD>; 3]], uses: &Uses<'t, TD>) -> Self {
+        let mut local = Uses::new();
+        for t in statements.iter().flat_map(|t| t.iter()) {
+            if let N3Term::Existential(_) = t {
+                *local.entry(t).or_insert(0) += 1;
+            }
+        }
+
+        // statements of `rdf:first` and of `rdf:rest`
+        let mut candidates: HashMap<&'t N3Term<TD>, (Vec<usize>, Vec<usize>)> = HashMap::new();
+        for (idx, [s, p, _]) in statements.iter().enumerate() {
+            if local.get(s) != Some(&3) || uses.get(s) != Some(&3) {
+                continue;
+            }
+            let entry = candidates.entry(s).or_default();
+            if is_iri(p, &rdf::iri::first) {
+                entry.0.push(idx);
+            } else if is_iri(p, &rdf::iri::rest) {
+                entry.1.push(idx);
+            }
+        }
+        let nodes: HashMap<_, _> = candidates
+            .into_iter()
+            .filter_map(
+                |(node, (first, rest))| match (first.as_slice(), rest.as_slice()) {
+                    ([first], [rest]) => Some((node, (*first, *rest))),
+                    _ => None,
+                },
+            )
+            .collect();
+        let referenced: HashSet<_> = nodes.values().map(|(_, r)| &statements[*r][2]).collect();
+
+        let mut lists = Self {
+            items: HashMap::new(),
+            consumed: HashSet::new(),
+        };
+        // as each node is referenced once there are no cycles
+        for head in nodes.keys().filter(|n| !referenced.contains(*n)) {
+            let mut items = vec![];
+            let mut consumed = vec![];
+            let mut node = *head;
+            let complete = loop {
+                let (first, rest) = match nodes.get(node) {
+                    Some(entry) => *entry,
+                    None => break false,
+                };
+                items.push(&statements[first][2]);
+                consumed.push(first);
+                consumed.push(rest);
+
+                node = &statements[rest][2];
+                if is_iri(node, &rdf::iri::nil) {
+                    break true;
+                }
+            };
+
+            if complete {
+                lists.items.insert(*head, items);
+                lists.consumed.extend(consumed);
+            }
+        }
+
+        lists
+    }
+}
+
+/// Counts the occurrences of existentials, including nested formulas.
+fn count_existentials<'t, TD>(statements: &'t [[N3Term<TD>; 3]], uses: &mut Uses<'t, TD>)
+where
+    TD: TermData,
+{
+    for t in statements.iter().flat_map(|t| t.iter()) {
+        match t {
+            N3Term::Existential(_) => *uses.entry(t).or_insert(0) += 1,
+            N3Term::Formula(f) => count_existentials(f, uses),
+            _ => {}
+        }
+    }
+}
+
+/// Checks if `t` is the given IRI.
+fn is_iri<TD>(t: &N3Term<TD>, iri: &Iri<&'static str>) -> bool
+where
+    TD: TermData,
+{
+    match t {
+        N3Term::Iri(i) => *i.value() == *iri.value(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::Result;
+    use crate::parse::n3::Parser;
+    use crate::serialize::Indentation;
+    use sophia::term::mown_str::MownStr;
+    use test_case::test_case;
+
+    fn serialize(
+        doc: &str,
+        config: &Config<N3>,
+    ) -> std::result::Result<String, Box<dyn std::error::Error>> {
+        let statements = Parser::new(doc).collect::<Result<Vec<_>>>()?;
+        let mut ser = Serializer::new(vec![], config)?;
+        ser.serialize(&statements)?;
+        Ok(String::from_utf8(ser.finish())?)
+    }
+
+    #[test]
+    fn check_serialize() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let doc = r#"@prefix : <http://example.org/> .
+        { ?x :p ( 1 "a\"b" ) . ?x :q [] } => { ?x a :C } .
+        :s :p { } .
+        "#;
+        let mut config = Config::<N3>::with_default_prefixes();
+        config.add_prefix("", "http://example.org/".into())?;
+        config.set_indentation(Indentation::spaces_2());
+
+        let expected = r#"@prefix : <http://example.org/> .
+@prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+
+{
+  ?x :p ( 1 "a\"b" ) .
+  ?x :q [] .
+} => {
+  ?x rdf:type :C .
+} .
+:s :p {} .
+"#;
+        assert_eq!(serialize(doc, &config)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn check_line_width() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let doc = r#"@prefix : <http://example.org/> .
+        { ?x :p :C } => { ?x :p :D } .
+        :s :p ( :aaaaaaaaaaaa :bbbbbbbbbbbb :cccccccccccc ) .
+        { ?x :p ( 1 2 ) . ?x :qqqqqqqqqqqq :rrrrrrrrrrrr } => { ?x :p :E } .
+        "#;
+        let mut config = Config::<N3>::default();
+        config.add_prefix("", "http://example.org/".into())?;
+        config.set_line_width(Some(40));
+
+        let expected = r#"@prefix : <http://example.org/> .
+
+{ ?x :p :C } => { ?x :p :D } .
+:s :p ( :aaaaaaaaaaaa
+        :bbbbbbbbbbbb
+        :cccccccccccc ) .
+{
+    ?x :p ( 1 2 ) .
+    ?x :qqqqqqqqqqqq :rrrrrrrrrrrr .
+} => { ?x :p :E } .
+"#;
+        let serialized = serialize(doc, &config)?;
+        assert_eq!(serialized, expected);
+        let parsed = Parser::new(&serialized).collect::<Result<Vec<_>>>()?;
+        assert_eq!(parsed.len(), 8);
+        Ok(())
+    }
+
+    #[test_case("<http://ex/s> <http://ex/p> <http://ex/o> ." ; "plain")]
+    #[test_case("<http://ex/s> <http://ex/p> { ?x <http://ex/p> { ?x <http://ex/q> \"o\"@en } } ." ; "nested formulas")]
+    #[test_case("{ ?x <http://ex/p> ?y } => { ?y <http://ex/p> ?x } ." ; "rule")]
+    #[test_case("<http://ex/s> <http://ex/p> \"a\\nb\\\\c\\td\"^^<http://ex/dt> ." ; "escaped literal")]
+    fn check_round_trip(doc: &str) {
+        let config = Config::<N3>::default();
+        let original = Parser::new(doc).collect::<Result<Vec<_>>>().unwrap();
+        let serialized = serialize(doc, &config).unwrap();
+        let parsed = Parser::new(&serialized)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(original, parsed);
+    }
+
+    #[test]
+    fn check_round_trip_existentials() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let doc = r#"@prefix : <http://example.org/> .
+        _:a :p _:a .
+        { ( ?x ( 1 2 ) ) :sum [] } => { _:b :p ( ) ; :q _:b } .
+        "#;
+        let mut config = Config::<N3>::default();
+        config.add_prefix("", "http://example.org/".into())?;
+
+        // serialize twice to get stable labels
+        let first = serialize(doc, &config)?;
+        let second = serialize(&first, &config)?;
+        assert_eq!(first, second);
+
+        let parsed = Parser::new(&first).collect::<Result<Vec<_>>>()?;
+        let original = Parser::new(doc).collect::<Result<Vec<_>>>()?;
+        assert!(isomorphic(&parsed, &original));
+        Ok(())
+    }
+
+    #[test_case("( 1 ( 2 ) ) <http://ex/p> () ." ; "nested lists")]
+    #[test_case("_:n rdf:rest <http://ex/a> . _:n rdf:rest rdf:nil . <http://ex/s> <http://ex/p> _:n ." ; "two rests")]
+    #[test_case("_:n rdf:first 1 . _:n rdf:first 2 . <http://ex/s> <http://ex/p> _:n ." ; "two firsts")]
+    #[test_case("_:n rdf:first 1 ; rdf:rest _:m . _:m rdf:first 2 . <http://ex/s> <http://ex/p> _:n ." ; "open end")]
+    fn check_round_trip_lists(doc: &str) {
+        let doc = format!(
+            "@prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> . {}",
+            doc
+        );
+        let config = Config::<N3>::default();
+        let original = Parser::new(&doc).collect::<Result<Vec<_>>>().unwrap();
+        let serialized = serialize(&doc, &config).unwrap();
+        let parsed = Parser::new(&serialized)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert!(isomorphic(&parsed, &original), "{}", serialized);
+    }
+
+    type Statements<'a> = [[N3Term<MownStr<'a>>; 3]];
+
+    /// Checks if the formulas are equal up to renaming of existentials.
+    fn isomorphic(a: &Statements<'_>, b: &Statements<'_>) -> bool {
+        let mut labels_a = vec![];
+        existentials(a, &mut labels_a);
+        let mut labels_b = vec![];
+        existentials(b, &mut labels_b);
+        if labels_a.len() != labels_b.len() {
+            return false;
+        }
+
+        let expected = normalize(b, &labels_b.iter().cloned().zip(0..).collect());
+        permutations(labels_a.len()).into_iter().any(|perm| {
+            let renaming = labels_a.iter().cloned().zip(perm).collect();
+            normalize(a, &renaming) == expected
+        })
+    }
+
+    /// Collects the distinct labels of all existentials.
+    fn existentials(statements: &Statements<'_>, labels: &mut Vec<String>) {
+        for t in statements.iter().flat_map(|t| t.iter()) {
+            match t {
+                N3Term::Existential(bn) => {
+                    let label = bn.value().to_string();
+                    if !labels.contains(&label) {
+                        labels.push(label);
+                    }
+                }
+                N3Term::Formula(f) => existentials(f, labels),
+                _ => {}
+            }
+        }
+    }
+
+    /// Writes the statements sorted with existentials renamed to numbers.
+    fn normalize(statements: &Statements<'_>, renaming: &HashMap<String, usize>) -> Vec<String> {
+        let mut normalized: Vec<String> = statements
+            .iter()
+            .map(|t| {
+                t.iter()
+                    .map(|t| match t {
+                        N3Term::Existential(bn) => format!("_:{}", renaming[&*bn.value()]),
+                        N3Term::Formula(f) => format!("{{ {} }}", normalize(f, renaming).join(" ")),
+                        other => format!("{:?}", other),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect();
+        normalized.sort();
+        normalized
+    }
+
+    /// All permutations of `0..n`.
+    fn permutations(n: usize) -> Vec<Vec<usize>> {
+        if n == 0 {
+            return vec![vec![]];
+        }
+        permutations(n - 1)
+            .into_iter()
+            .flat_map(|perm| {
+                (0..n).map(move |pos| {
+                    let mut perm = perm.clone();
+                    perm.insert(pos, n - 1);
+                    perm
+                })
+            })
+            .collect()
+    }
+}
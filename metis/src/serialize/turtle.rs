@@ -1,12 +1,24 @@
 //! Serialize the turtle format.
+//!
+//! The `Serializer` writes triples as they come and only groups consecutive
+//! triples. The `BufferedSerializer` groups triples by subject within a
+//! memory budget. The `PrettySerializer` collects the whole graph first and
+//! writes it as compact as possible. The `TurtleSerializer` provides the
+//! latter through `sophia`'s `TripleSerializer` and `Stringifier` traits.
 
+mod _buffered;
+mod _canonical;
+mod _pretty;
+mod _sophia;
 mod _stream;
+pub use self::_buffered::*;
+pub use self::_pretty::*;
+pub use self::_sophia::*;
 pub use self::_stream::*;
 
-use crate::common::Prolog;
 use crate::serialize::{Config, Serializable};
 use crate::Turtle;
-use sophia::term::{Term, TermData};
+use sophia::term::{BoxTerm, Term, TermData};
 use std::io;
 
 impl<TD> Serializable<Turtle> for Term<TD>
@@ -15,58 +27,88 @@ where
 {
     type Error = io::Error;
 
-    fn serialize<U>(
+    fn serialize(
         &self,
         target: &mut impl io::Write,
-        _: &Config<Turtle, U>,
-    ) -> Result<(), Self::Error>
-    where
-        U: TermData,
-    {
+        conf: &Config<Turtle>,
+    ) -> Result<(), Self::Error> {
         match self {
-            Term::Iri(iri) => {
-                // unimplemented!("Needs progress in `sophia` (term.match_ns())")
-                write!(target, "<{}>", iri)?
-            }
-            Term::Literal(txt, _) => {
-                // unimplemented!("Needs progress in `sophia`")
-                write!(target, "\"{}\"", txt.as_ref())?
-            }
-            Term::BNode(label) => write!(target, "_:{}", label.as_ref())?,
-            Term::Variable(name) => write!(target, "?{}", name.as_ref())?,
+            Term::Iri(iri) => conf.write_iri(target, &iri.value())?,
+            Term::Literal(lit) => conf.write_literal(target, lit)?,
+            Term::BNode(bn) => write!(target, "_:{}", bn.value())?,
+            Term::Variable(var) => write!(target, "?{}", var.value())?,
         };
 
         Ok(())
     }
 }
 
-impl<TD: TermData> Prolog<Turtle, TD> {
-    /// Write the preamble according to the `prolog` to the target.
-    #[allow(clippy::useless_let_if_seq)]
-    pub fn write_preamble<T>(&self, target: &mut T) -> io::Result<()>
-    where
-        T: io::Write,
-    {
-        // this is more clear than clippy's suggestion
-        let mut wrote_something = false;
+/// Checks if `t` is a blank node.
+fn is_bnode(t: &BoxTerm) -> bool {
+    matches!(t, Term::BNode(_))
+}
 
-        if !self.prefixes.is_empty() {
-            self.prefixes
-                .iter()
-                .map(|(p, ns)| writeln!(target, "@prefix {}: <{}> .", p.as_ref(), ns.as_ref()))
-                .collect::<io::Result<Vec<()>>>()?;
-            wrote_something = true;
-        }
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse::turtle::Parser;
+    use test_case::test_case;
 
-        if let Some(base) = &self.base {
-            writeln!(target, "@base <{}> .", base.as_ref())?;
-            wrote_something = true;
-        }
+    #[test]
+    fn check_iri() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let mut config = Config::<Turtle>::with_default_prefixes();
+        config.add_prefix("ex", "http://example.org/".into())?;
 
-        if wrote_something {
-            writeln!(target)?;
+        let mut target = vec![];
+        for iri in &[
+            "http://www.w3.org/1999/02/22-rdf-syntax-ns#type",
+            "http://example.org/a.b",
+            "http://example.org/path/to",
+            "http://example.com/",
+        ] {
+            BoxTerm::new_iri(*iri)?.serialize(&mut target, &config)?;
+            config.write_space(&mut target)?;
         }
-
+        assert_eq!(
+            String::from_utf8(target)?,
+            "rdf:type ex:a.b ex:path\\/to <http://example.com/> "
+        );
         Ok(())
     }
+
+    #[test_case(r#""abc""# => r#""abc""# ; "simple")]
+    #[test_case(r#""abc"@en-US"# => r#""abc"@en-US"# ; "language")]
+    #[test_case(r#""abc"^^xsd:string"# => r#""abc""# ; "string")]
+    #[test_case(r#"'a"b\\c'"# => r#""a\"b\\c""# ; "escaped")]
+    #[test_case(r#""a\tb\rc\u0001""# => r#""a\tb\rc\u0001""# ; "control")]
+    #[test_case(r#""a\nb""# => "\"\"\"a\nb\"\"\"" ; "long")]
+    #[test_case("'''a\"b\"\"c\"\n'''" => "\"\"\"a\"b\\\"\"c\"\n\"\"\"" ; "long with quotes")]
+    #[test_case("'''a\nb\"'''" => "\"\"\"a\nb\\\"\"\"\"" ; "long with final quote")]
+    #[test_case("-12" => "-12" ; "integer")]
+    #[test_case("1.5" => "1.5" ; "decimal")]
+    #[test_case("1.5E-3" => "1.5E-3" ; "double")]
+    #[test_case("true" => "true" ; "boolean")]
+    #[test_case(r#""1.0"^^xsd:integer"# => r#""1.0"^^xsd:integer"# ; "invalid integer")]
+    #[test_case(r#""yes"^^xsd:boolean"# => r#""yes"^^xsd:boolean"# ; "invalid boolean")]
+    #[test_case(r#""x"^^<http://example.com/dt>"# => r#""x"^^<http://example.com/dt>"# ; "datatype")]
+    fn check_literal(lit: &str) -> String {
+        let config = Config::<Turtle>::with_default_prefixes();
+        let parse = |lit: &str| -> BoxTerm {
+            let doc = format!(
+                "@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .\n<http://ex/s> <http://ex/p> {} .",
+                lit
+            );
+            let [_, _, o] = Parser::new(&doc).next().unwrap().unwrap();
+            o.clone_into()
+        };
+        let o = parse(lit);
+
+        let mut target = vec![];
+        o.serialize(&mut target, &config).unwrap();
+        let serialized = String::from_utf8(target).unwrap();
+        // the serialized literal is read back the same
+        assert_eq!(o, parse(&serialized));
+
+        serialized
+    }
 }
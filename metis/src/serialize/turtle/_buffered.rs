@@ -0,0 +1,214 @@
+//! Implementation of a buffered serializer for Turtle.
+//!
+//! Triples are collected and grouped by subject until the memory budget is
+//! exceeded. Then all groups are written and the buffer is cleared. Therefore,
+//! input in arbitrary order, e.g. from hash-based graphs, gives compact
+//! output as long as the graph fits into the budget.
+
+use super::Turtle;
+use crate::error::{Error, Result};
+use crate::serialize::{Config, Serializable};
+use sophia::term::BoxTerm;
+use sophia::triple::{
+    stream::{SinkError, SourceError, StreamError},
+    Triple,
+};
+use std::collections::HashMap;
+use std::io;
+use std::mem;
+
+/// Default memory budget of a `BufferedSerializer` in bytes.
+pub const DEFAULT_BUDGET: usize = 16 * 1024 * 1024;
+
+/// The triples of a subject.
+struct Group {
+    subject: BoxTerm,
+    /// Predicates in order of appearance with their objects.
+    predicates: Vec<(BoxTerm, Vec<BoxTerm>)>,
+}
+
+/// Serializer that groups triples by subject before writing them.
+pub struct BufferedSerializer<'a, T>
+where
+    T: io::Write,
+{
+    target: T,
+    config: &'a Config<Turtle>,
+    budget: usize,
+    used: usize,
+    groups: Vec<Group>,
+    /// Index of the group of each subject.
+    index: HashMap<BoxTerm, usize>,
+}
+
+impl<'a, T> BufferedSerializer<'a, T>
+where
+    T: io::Write,
+{
+    /// Create a new serializer with the
+    /// [`DEFAULT_BUDGET`](constant.DEFAULT_BUDGET.html).
+    ///
+    /// The preamble for `config` is written immediately.
+    pub fn new(target: T, config: &'a Config<Turtle>) -> io::Result<Self> {
+        Self::with_budget(target, config, DEFAULT_BUDGET)
+    }
+    /// Create a new serializer that buffers up to approximately `budget`
+    /// bytes of triples.
+    ///
+    /// The preamble for `config` is written immediately.
+    pub fn with_budget(target: T, config: &'a Config<Turtle>, budget: usize) -> io::Result<Self> {
+        let mut target = target;
+        config.write_preamble(&mut target)?;
+
+        Ok(Self {
+            target,
+            config,
+            budget,
+            used: 0,
+            groups: vec![],
+            index: HashMap::new(),
+        })
+    }
+    /// Serialize a triple source to the serializer's target.
+    ///
+    /// Buffered triples are written when the budget is exceeded and when the
+    /// source is exhausted.
+    pub fn serialize<TS, Tri, E>(&mut self, ts: TS) -> Result<(), StreamError<E, Error>>
+    where
+        TS: Iterator<Item = Result<Tri, E>>,
+        E: std::error::Error,
+        Tri: Triple,
+    {
+        for tri in ts {
+            let tri = tri.map_err(SourceError)?;
+            self.push([
+                tri.s().clone_into(),
+                tri.p().clone_into(),
+                tri.o().clone_into(),
+            ]);
+
+            if self.used > self.budget {
+                self.flush().map_err(|e| SinkError(Error::from(e)))?;
+            }
+        }
+
+        self.flush().map_err(|e| SinkError(Error::from(e)))
+    }
+    /// Drop the serializer and get the target back.
+    pub fn finish(self) -> T {
+        self.target
+    }
+    /// Add a triple to the buffer.
+    fn push(&mut self, [s, p, o]: [BoxTerm; 3]) {
+        let idx = match self.index.get(&s) {
+            Some(idx) => *idx,
+            None => {
+                self.used += 2 * term_size(&s);
+                self.groups.push(Group {
+                    subject: s.clone(),
+                    predicates: vec![],
+                });
+                self.index.insert(s, self.groups.len() - 1);
+                self.groups.len() - 1
+            }
+        };
+        let used = &mut self.used;
+        let predicates = &mut self.groups[idx].predicates;
+
+        match predicates.iter_mut().find(|(pred, _)| *pred == p) {
+            Some((_, objects)) if objects.contains(&o) => {}
+            Some((_, objects)) => {
+                *used += term_size(&o);
+                objects.push(o);
+            }
+            None => {
+                *used += term_size(&p) + term_size(&o);
+                predicates.push((p, vec![o]));
+            }
+        }
+    }
+    /// Write and clear the buffer.
+    fn flush(&mut self) -> io::Result<()> {
+        self.index.clear();
+        self.used = 0;
+
+        for group in mem::take(&mut self.groups) {
+            self.write_group(&group)?;
+        }
+
+        Ok(())
+    }
+    /// Write all triples of a subject.
+    fn write_group(&mut self, group: &Group) -> io::Result<()> {
+        let target = &mut self.target;
+
+        group.subject.serialize(target, self.config)?;
+        for (i, (p, objects)) in group.predicates.iter().enumerate() {
+            if i == 0 {
+                self.config.write_space(target)?;
+            } else {
+                target.write_all(b" ;\n")?;
+                self.config.write_indent(target)?;
+            }
+            p.serialize(target, self.config)?;
+
+            for (j, o) in objects.iter().enumerate() {
+                if j > 0 {
+                    target.write_all(b",")?;
+                }
+                self.config.write_space(target)?;
+                o.serialize(target, self.config)?;
+            }
+        }
+
+        target.write_all(b" .\n\n")
+    }
+}
+
+/// Approximate memory used by a term.
+fn term_size(t: &BoxTerm) -> usize {
+    mem::size_of::<BoxTerm>() + t.value().len()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse::turtle::Parser;
+
+    const DOC: &str = r#"
+        <http://example.org/s1> <http://example.org/p1> <http://example.org/o1> .
+        <http://example.org/s2> <http://example.org/p1> "lit" .
+        <http://example.org/s1> <http://example.org/p2> <http://example.org/o2> .
+        <http://example.org/s1> <http://example.org/p1> <http://example.org/o3> .
+        <http://example.org/s1> <http://example.org/p1> <http://example.org/o1> .
+    "#;
+
+    #[test]
+    fn check_grouping() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let config = Config::<Turtle>::default();
+        let mut ser = BufferedSerializer::new(vec![], &config)?;
+        ser.serialize(Parser::new(DOC))?;
+
+        let turtle = String::from_utf8(ser.finish())?;
+        assert_eq!(
+            turtle,
+            "<http://example.org/s1> <http://example.org/p1> <http://example.org/o1>, <http://example.org/o3> ;\n    \
+             <http://example.org/p2> <http://example.org/o2> .\n\n\
+             <http://example.org/s2> <http://example.org/p1> \"lit\" .\n\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn check_budget() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let config = Config::<Turtle>::default();
+        let mut ser = BufferedSerializer::with_budget(vec![], &config, 0)?;
+        ser.serialize(Parser::new(DOC))?;
+
+        let turtle = String::from_utf8(ser.finish())?;
+        assert_eq!(turtle.matches(" .\n\n").count(), 5);
+        let reparsed = Parser::new(&turtle).collect::<Result<Vec<_>>>()?;
+        assert_eq!(reparsed.len(), 5);
+        Ok(())
+    }
+}
@@ -0,0 +1,429 @@
+//! Canonical labels for blank nodes.
+//!
+//! The labels are derived from the structure of the graph by color
+//! refinement. Each blank node is colored by the triples it occurs in, where
+//! other blank nodes are represented by their color of the previous round.
+//! This is repeated until the coloring is stable.
+//!
+//! If blank nodes still share a color, each of them in turn gets a color of
+//! its own and the refinement continues. Of all resulting labelings the one
+//! that gives the smallest sorted list of triples is chosen.
+//!
+//! Branches that are mapped onto each other by an automorphism of the graph
+//! give the same labelings, so only one of them is searched. Before a
+//! branch is searched, the first labeling reachable from it is compared
+//! with the first labelings of the branches already searched. If they label
+//! the graph equally, they define an automorphism and the branch is skipped.
+
+use super::is_bnode;
+use sophia::term::{blank_node::BlankNode, BoxTerm};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::hash::Hash;
+
+/// Color of each blank node.
+type Colors<'g> = HashMap<&'g BoxTerm, usize>;
+
+/// A term of a triple as it is compared during the search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Label {
+    /// The blank node whose signature is computed.
+    This,
+    /// A blank node with the given color.
+    Node(usize),
+    /// Any other term, given by the rank of its N-Triples form.
+    Term(usize),
+}
+
+/// Replaces the labels of all blank nodes by canonical labels `c0`, `c1`, ...
+pub(crate) fn relabel(triples: &mut [[BoxTerm; 3]]) {
+    let labels: HashMap<BoxTerm, BoxTerm> = Graph::new(triples)
+        .canonical_colors()
+        .into_iter()
+        .map(|(bn, color)| {
+            let label = BlankNode::<Box<str>>::new_unchecked(format!("c{}", color));
+            (bn.clone(), label.into())
+        })
+        .collect();
+
+    for term in triples.iter_mut().flat_map(|t| t.iter_mut()) {
+        if let Some(label) = labels.get(term) {
+            *term = label.clone();
+        }
+    }
+}
+
+/// A graph prepared for the computation of canonical colors.
+struct Graph<'g> {
+    triples: &'g [[BoxTerm; 3]],
+    /// The triples for fast lookup.
+    set: HashSet<&'g [BoxTerm; 3]>,
+    /// Indices of the triples each blank node occurs in.
+    occurrences: HashMap<&'g BoxTerm, Vec<usize>>,
+    /// Rank of all other terms in the ordered set of their N-Triples forms.
+    terms: HashMap<&'g BoxTerm, usize>,
+}
+
+impl<'g> Graph<'g> {
+    fn new(triples: &'g [[BoxTerm; 3]]) -> Self {
+        let mut occurrences: HashMap<&BoxTerm, Vec<usize>> = HashMap::new();
+        let mut names: HashMap<&BoxTerm, String> = HashMap::new();
+        for (idx, t) in triples.iter().enumerate() {
+            for term in t.iter() {
+                if is_bnode(term) {
+                    let occ = occurrences.entry(term).or_default();
+                    if occ.last() != Some(&idx) {
+                        occ.push(idx);
+                    }
+                } else {
+                    names.entry(term).or_insert_with(|| term.to_string());
+                }
+            }
+        }
+        let order: HashMap<&String, usize> = names
+            .values()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .enumerate()
+            .map(|(idx, name)| (name, idx))
+            .collect();
+        let terms = names
+            .iter()
+            .map(|(term, name)| (*term, order[name]))
+            .collect();
+
+        Self {
+            triples,
+            set: triples.iter().collect(),
+            occurrences,
+            terms,
+        }
+    }
+
+    /// Computes a unique color for each blank node.
+    fn canonical_colors(&self) -> Colors<'g> {
+        let mut colors: Colors = self.occurrences.keys().map(|bn| (*bn, 0)).collect();
+        self.refine(&mut colors);
+
+        let mut best = None;
+        self.search(colors, &mut best);
+        best.map(|(_, colors)| colors).unwrap_or_default()
+    }
+
+    /// Individualizes the blank nodes of the smallest shared color until all
+    /// colors are unique and keeps the coloring with the smallest labeled
+    /// graph.
+    ///
+    /// Returns the first coloring reached, i.e. the one that always
+    /// individualizes the first candidate.
+    fn search(
+        &self,
+        colors: Colors<'g>,
+        best: &mut Option<(Vec<[Label; 3]>, Colors<'g>)>,
+    ) -> Colors<'g> {
+        let candidates = match self.candidates(&colors) {
+            Some(candidates) => candidates,
+            None => {
+                let labeled = self.labeled_graph(&colors);
+                if best
+                    .as_ref()
+                    .map_or(true, |(smallest, _)| labeled < *smallest)
+                {
+                    *best = Some((labeled, colors.clone()));
+                }
+                return colors;
+            }
+        };
+
+        // the searched candidates with the first coloring of their branch
+        let mut searched: Vec<(&BoxTerm, Colors<'g>, Vec<[Label; 3]>)> = vec![];
+        for chosen in candidates {
+            if searched
+                .iter()
+                .any(|(other, _, _)| self.is_swappable(chosen, other))
+            {
+                continue;
+            }
+            let branch = self.individualize(&colors, chosen);
+            if !searched.is_empty() {
+                let first = self.first_leaf(branch.clone());
+                let labeled = self.labeled_graph(&first);
+                if searched.iter().any(|(other, other_first, other_labeled)| {
+                    labeled == *other_labeled
+                        && is_automorphism(&first, other_first, &colors, chosen, other)
+                }) {
+                    continue;
+                }
+            }
+            let first = self.search(branch, best);
+            let labeled = self.labeled_graph(&first);
+            searched.push((chosen, first, labeled));
+        }
+
+        let (_, first, _) = searched.swap_remove(0);
+        first
+    }
+
+    /// Follows the first candidate until all colors are unique.
+    fn first_leaf(&self, mut colors: Colors<'g>) -> Colors<'g> {
+        while let Some(candidates) = self.candidates(&colors) {
+            colors = self.individualize(&colors, candidates[0]);
+        }
+        colors
+    }
+
+    /// Returns the blank nodes of the smallest shared color or `None` if all
+    /// colors are unique.
+    fn candidates(&self, colors: &Colors<'g>) -> Option<Vec<&'g BoxTerm>> {
+        let mut counts: HashMap<usize, usize> = HashMap::new();
+        for color in colors.values() {
+            *counts.entry(*color).or_insert(0) += 1;
+        }
+        let shared = counts
+            .iter()
+            .filter(|(_, count)| **count > 1)
+            .map(|(color, _)| *color)
+            .min()?;
+
+        let mut candidates: Vec<&BoxTerm> = colors
+            .iter()
+            .filter(|(_, color)| **color == shared)
+            .map(|(bn, _)| *bn)
+            .collect();
+        candidates.sort_by_cached_key(|bn| bn.to_string());
+        Some(candidates)
+    }
+
+    /// Gives `chosen` a color of its own and refines the coloring.
+    fn individualize(&self, colors: &Colors<'g>, chosen: &BoxTerm) -> Colors<'g> {
+        let mut branch = rank(
+            colors
+                .iter()
+                .map(|(bn, color)| (*bn, (*color, *bn != chosen)))
+                .collect(),
+        );
+        self.refine(&mut branch);
+        branch
+    }
+
+    /// Checks if swapping the blank nodes `a` and `b` leaves the graph
+    /// unchanged.
+    fn is_swappable(&self, a: &BoxTerm, b: &BoxTerm) -> bool {
+        let swap = |t: &BoxTerm| {
+            if t == a {
+                b.clone()
+            } else if t == b {
+                a.clone()
+            } else {
+                t.clone()
+            }
+        };
+
+        self.occurrences[a].len() == self.occurrences[b].len()
+            && self.occurrences[a].iter().all(|idx| {
+                let [s, p, o] = &self.triples[*idx];
+                self.set.contains(&[swap(s), swap(p), swap(o)])
+            })
+    }
+
+    /// Returns `t` as it is compared, `this` is the blank node whose
+    /// signature is computed.
+    fn label(&self, t: &BoxTerm, colors: &Colors<'g>, this: Option<&BoxTerm>) -> Label {
+        if Some(t) == this {
+            Label::This
+        } else if let Some(color) = colors.get(t) {
+            Label::Node(*color)
+        } else {
+            Label::Term(self.terms[t])
+        }
+    }
+
+    /// Returns the sorted triples with blank nodes labeled by their color.
+    fn labeled_graph(&self, colors: &Colors<'g>) -> Vec<[Label; 3]> {
+        let mut labeled: Vec<[Label; 3]> = self
+            .triples
+            .iter()
+            .map(|[s, p, o]| {
+                [
+                    self.label(s, colors, None),
+                    self.label(p, colors, None),
+                    self.label(o, colors, None),
+                ]
+            })
+            .collect();
+        labeled.sort();
+        labeled
+    }
+
+    /// Refines the coloring until the number of colors is stable.
+    fn refine(&self, colors: &mut Colors<'g>) {
+        let mut count = count_colors(colors);
+
+        loop {
+            let signatures: HashMap<_, _> = self
+                .occurrences
+                .iter()
+                .map(|(bn, occ)| {
+                    let mut edges: Vec<[Label; 3]> = occ
+                        .iter()
+                        .map(|idx| {
+                            let [s, p, o] = &self.triples[*idx];
+                            [
+                                self.label(s, colors, Some(*bn)),
+                                self.label(p, colors, Some(*bn)),
+                                self.label(o, colors, Some(*bn)),
+                            ]
+                        })
+                        .collect();
+                    edges.sort();
+                    (*bn, (colors[bn], edges))
+                })
+                .collect();
+
+            *colors = rank(signatures);
+            let new_count = count_colors(colors);
+            if new_count == count {
+                return;
+            }
+            count = new_count;
+        }
+    }
+}
+
+/// Checks if the colorings `a` and `b` that label the graph equally define
+/// an automorphism that maps `a_chosen` to `b_chosen` and keeps the coloring
+/// `colors` they were derived from.
+///
+/// As `a` and `b` are discrete and label the graph equally, mapping each
+/// blank node to the one of the same color in the other coloring preserves
+/// the triples.
+fn is_automorphism(
+    a: &Colors,
+    b: &Colors,
+    colors: &Colors,
+    a_chosen: &BoxTerm,
+    b_chosen: &BoxTerm,
+) -> bool {
+    let by_color: HashMap<usize, &BoxTerm> = b.iter().map(|(bn, color)| (*color, *bn)).collect();
+    let map = |bn: &BoxTerm| by_color[&a[bn]];
+
+    map(a_chosen) == b_chosen && a.keys().all(|bn| colors[map(bn)] == colors[*bn])
+}
+
+/// Replaces the signatures by their position in the ordered set of all
+/// signatures.
+fn rank<'g, K>(signatures: HashMap<&'g BoxTerm, K>) -> Colors<'g>
+where
+    K: Ord + Hash,
+{
+    let order: HashMap<&K, usize> = signatures
+        .values()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .enumerate()
+        .map(|(idx, sig)| (sig, idx))
+        .collect();
+
+    signatures
+        .iter()
+        .map(|(bn, sig)| (*bn, order[sig]))
+        .collect()
+}
+
+/// Returns the number of distinct colors.
+fn count_colors(colors: &Colors) -> usize {
+    colors.values().collect::<BTreeSet<_>>().len()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::Result;
+    use crate::parse::turtle::Parser;
+    use std::time::{Duration, Instant};
+    use test_case::test_case;
+
+    fn canonical(doc: &str) -> Vec<String> {
+        let mut triples: Vec<[BoxTerm; 3]> = Parser::new(doc)
+            .map(|t| t.map(|[s, p, o]| [s.clone_into(), p.clone_into(), o.clone_into()]))
+            .collect::<Result<_>>()
+            .unwrap();
+        relabel(&mut triples);
+        let mut nt: Vec<String> = triples
+            .iter()
+            .map(|[s, p, o]| format!("{} {} {} .", s, p, o))
+            .collect();
+        nt.sort();
+        nt
+    }
+
+    #[test_case(
+        "_:x <p:p> _:y . _:y <p:p> _:x . <p:s> <p:q> _:x, _:y . _:x <p:v> 1 . _:y <p:v> 2 .",
+        "_:b <p:v> 1 . _:a <p:v> 2 . <p:s> <p:q> _:a, _:b . _:a <p:p> _:b . _:b <p:p> _:a ."
+        ; "distinguishable"
+    )]
+    #[test_case(
+        "<p:s> <p:p> _:a, _:b . _:a <p:q> _:b . _:b <p:q> _:a .",
+        "_:n <p:q> _:m . <p:s> <p:p> _:n, _:m . _:m <p:q> _:n ."
+        ; "symmetric"
+    )]
+    #[test_case(
+        "<p:s> <p:p> [ <p:q> [] ], [ <p:q> [] ], [ <p:r> 1 ] .",
+        "<p:s> <p:p> [ <p:r> 1 ], [ <p:q> _:x ], [ <p:q> _:y ] ."
+        ; "trees"
+    )]
+    #[test_case(
+        "_:a <p:p> _:b . _:b <p:p> _:c . _:c <p:p> _:a .
+         _:d <p:p> _:e . _:e <p:p> _:f . _:f <p:p> _:g . _:g <p:p> _:h . _:h <p:p> _:i . _:i <p:p> _:d .",
+        "_:g <p:p> _:h . _:h <p:p> _:i . _:i <p:p> _:g .
+         _:a <p:p> _:b . _:b <p:p> _:c . _:c <p:p> _:d . _:d <p:p> _:e . _:e <p:p> _:f . _:f <p:p> _:a ."
+        ; "regular"
+    )]
+    fn check_isomorphic(doc1: &str, doc2: &str) {
+        assert_eq!(canonical(doc1), canonical(doc2));
+    }
+
+    #[test]
+    fn check_not_isomorphic() {
+        let two_triangles = "_:a <p:p> _:b . _:b <p:p> _:c . _:c <p:p> _:a .
+            _:d <p:p> _:e . _:e <p:p> _:f . _:f <p:p> _:d .";
+        let hexagon = "_:a <p:p> _:b . _:b <p:p> _:c . _:c <p:p> _:d .
+            _:d <p:p> _:e . _:e <p:p> _:f . _:f <p:p> _:a .";
+        let hexagon_relabeled = "_:f <p:p> _:d . _:d <p:p> _:b . _:b <p:p> _:a .
+            _:a <p:p> _:e . _:e <p:p> _:c . _:c <p:p> _:f .";
+        assert_ne!(canonical(two_triangles), canonical(hexagon));
+        assert_eq!(canonical(hexagon), canonical(hexagon_relabeled));
+    }
+
+    #[test]
+    fn check_identical_subtrees() {
+        let n = 10;
+        let anonymous = format!("<p:s> <p:p> {} .", vec!["[ <p:q> [] ]"; n].join(", "));
+        let labeled: String = (0..n)
+            .rev()
+            .map(|i| format!("<p:s> <p:p> _:x{} . _:x{} <p:q> _:y{} .", i, i, i * 7 % n))
+            .collect();
+
+        let start = Instant::now();
+        let nt = canonical(&anonymous);
+        assert!(
+            start.elapsed() < Duration::from_secs(5),
+            "Took {:?}",
+            start.elapsed()
+        );
+        assert_eq!(nt.len(), 2 * n);
+        assert_eq!(nt, canonical(&labeled));
+    }
+
+    #[test]
+    fn check_distinct_labels() {
+        let nt = canonical("<p:s> <p:p> [], [], [] .");
+        assert_eq!(
+            nt,
+            vec![
+                "<p:s> <p:p> _:c0 .",
+                "<p:s> <p:p> _:c1 .",
+                "<p:s> <p:p> _:c2 .",
+            ]
+        );
+    }
+}
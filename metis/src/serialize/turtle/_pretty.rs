@@ -0,0 +1,703 @@
+//! Implementation of a pretty serializer for Turtle.
+//!
+//! The whole graph is collected before anything is written. This allows to
+//! write the graph in a compact form:
+//!
+//! - Subjects and predicates are written in a deterministic order. The
+//!   predicates of the `Config`'s predicate order come first, by default
+//!   `rdf:type` which is written as `a`.
+//! - Blank nodes that are referenced exactly once are written inline as
+//!   `[ ... ]`.
+//! - Well-formed `rdf:first`/`rdf:rest` chains are written as `( ... )`.
+//! - Blank nodes that are never referenced are written as top-level
+//!   `[ ... ] .` blocks.
+//! - If a line width is set, objects, collections and nested blocks that fit
+//!   are kept on one line. Longer object lists and collections are written
+//!   one item per line.
+
+use super::{_canonical, is_bnode, Turtle};
+use crate::error::{Error, Result};
+use crate::serialize::_layout::{ColumnWriter, FlatWriter};
+use crate::serialize::{Config, Serializable};
+use sophia::ns::rdf;
+use sophia::term::{iri::Iri, BoxTerm, Term};
+use sophia::triple::{
+    stream::{SinkError, SourceError, StreamError},
+    Triple,
+};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::ops::Range;
+
+/// Serializer that collects a whole graph and writes it pretty.
+pub struct PrettySerializer<'a, T>
+where
+    T: io::Write,
+{
+    target: ColumnWriter<T>,
+    config: &'a Config<Turtle>,
+    /// Write everything on one line.
+    flat: bool,
+}
+
+impl<'a, T> PrettySerializer<'a, T>
+where
+    T: io::Write,
+{
+    /// Create a new serializer for the given target with the given
+    /// configuration.
+    ///
+    /// The preamble for `config` is written immediately.
+    pub fn new(target: T, config: &'a Config<Turtle>) -> io::Result<Self> {
+        let mut target = ColumnWriter::new(target);
+        config.write_preamble(&mut target)?;
+
+        Ok(Self {
+            target,
+            config,
+            flat: false,
+        })
+    }
+    /// Serialize a triple source to the serializer's target.
+    ///
+    /// All triples are collected before the graph is written.
+    pub fn serialize<TS, Tri, E>(&mut self, ts: TS) -> Result<(), StreamError<E, Error>>
+    where
+        TS: Iterator<Item = Result<Tri, E>>,
+        E: std::error::Error,
+        Tri: Triple,
+    {
+        let mut triples: Vec<[BoxTerm; 3]> = vec![];
+        for tri in ts {
+            let tri = tri.map_err(SourceError)?;
+            triples.push([
+                tri.s().clone_into(),
+                tri.p().clone_into(),
+                tri.o().clone_into(),
+            ]);
+        }
+
+        self.write_graph(triples)
+            .map_err(|e| SinkError(Error::from(e)))
+    }
+    /// Drop the serializer and get the target back.
+    pub fn finish(self) -> T {
+        self.target.into_inner()
+    }
+    /// Write a whole graph.
+    pub(super) fn write_graph(&mut self, mut triples: Vec<[BoxTerm; 3]>) -> io::Result<()> {
+        let order = self.config.predicate_order();
+        let sort = |triples: &mut Vec<[BoxTerm; 3]>| {
+            triples.sort_by_cached_key(|[s, p, o]| {
+                (s.to_string(), predicate_key(p, order), o.to_string())
+            })
+        };
+        sort(&mut triples);
+        triples.dedup();
+        if self.config.is_canonical() {
+            _canonical::relabel(&mut triples);
+            sort(&mut triples);
+        }
+        let index = Index::new(&triples);
+        let mut written = Written::default();
+
+        let mut first = true;
+        for &s in index.order.iter() {
+            if index.is_inlined(s) {
+                continue;
+            }
+            if !first {
+                self.target.write_all(b"\n")?;
+            }
+            first = false;
+            self.write_block(s, &index, &mut written)?;
+        }
+        // blank nodes that only reference each other
+        for &s in index.order.iter() {
+            if written.contains(s) {
+                continue;
+            }
+            if !first {
+                self.target.write_all(b"\n")?;
+            }
+            first = false;
+            self.write_block(s, &index, &mut written)?;
+        }
+
+        Ok(())
+    }
+    /// Write a top-level block of a subject.
+    fn write_block<'g>(
+        &mut self,
+        s: &'g BoxTerm,
+        index: &Index<'g>,
+        written: &mut Written<'g>,
+    ) -> io::Result<()> {
+        written.insert(s);
+
+        if index.refs(s) == 0 && is_bnode(s) {
+            self.write_property_list(s, 0, index, written)?;
+        } else {
+            s.serialize(&mut self.target, self.config)?;
+            self.config.write_space(&mut self.target)?;
+            self.write_predicates(s, 1, index, written)?;
+        }
+        self.target.write_all(b" .\n")
+    }
+    /// Write the predicates and objects of a subject.
+    ///
+    /// Each predicate except the first is written on a new line with the
+    /// given level of indentation.
+    fn write_predicates<'g>(
+        &mut self,
+        s: &'g BoxTerm,
+        level: usize,
+        index: &Index<'g>,
+        written: &mut Written<'g>,
+    ) -> io::Result<()> {
+        let mut triples = index.triples_of(s);
+
+        let mut first = true;
+        while let Some([_, p, _]) = triples.first() {
+            let len = triples.iter().take_while(|[_, q, _]| q == p).count();
+            let (objects, rest) = triples.split_at(len);
+            triples = rest;
+
+            if !first {
+                self.target.write_all(b" ;")?;
+                if self.flat {
+                    self.config.write_space(&mut self.target)?;
+                } else {
+                    self.target.write_all(b"\n")?;
+                    self.indent(level)?;
+                }
+            }
+            first = false;
+            self.write_predicate(p)?;
+            self.config.write_space(&mut self.target)?;
+            self.write_objects(objects, level, index, written)?;
+        }
+
+        Ok(())
+    }
+    /// Write a predicate, `rdf:type` as `a` if configured.
+    fn write_predicate(&mut self, p: &BoxTerm) -> io::Result<()> {
+        if self.config.is_type_as_a() && is_iri(p, &rdf::iri::type_) {
+            self.target.write_all(b"a")
+        } else {
+            p.serialize(&mut self.target, self.config)
+        }
+    }
+    /// Write the objects of a predicate.
+    ///
+    /// If the objects do not fit on the line, each is written on a line of
+    /// its own aligned under the first.
+    fn write_objects<'g>(
+        &mut self,
+        objects: &'g [[BoxTerm; 3]],
+        level: usize,
+        index: &Index<'g>,
+        written: &mut Written<'g>,
+    ) -> io::Result<()> {
+        if objects.len() > 1
+            && self.try_flat(written, |ser, written| {
+                ser.write_objects(objects, level, index, written)
+            })?
+        {
+            return Ok(());
+        }
+        let wrap = self.config.line_width().is_some() && !self.flat;
+
+        let column = self.target.column();
+        for (i, [_, _, o]) in objects.iter().enumerate() {
+            if i > 0 {
+                self.target.write_all(b",")?;
+                if wrap {
+                    self.target.align(column)?;
+                } else {
+                    self.config.write_space(&mut self.target)?;
+                }
+            }
+            self.write_object(o, level, index, written)?;
+        }
+
+        Ok(())
+    }
+    /// Write an object, inline if possible.
+    fn write_object<'g>(
+        &mut self,
+        o: &'g BoxTerm,
+        level: usize,
+        index: &Index<'g>,
+        written: &mut Written<'g>,
+    ) -> io::Result<()> {
+        if is_iri(o, &rdf::iri::nil) {
+            return self.target.write_all(b"()");
+        }
+        if !index.is_inlined(o) || written.contains(o) {
+            return o.serialize(&mut self.target, self.config);
+        }
+
+        if let Some(items) = index.list_items(o, written) {
+            for (node, _) in items.iter() {
+                written.insert(node);
+            }
+            self.write_collection(&items, level, index, written)
+        } else {
+            written.insert(o);
+            self.write_property_list(o, level, index, written)
+        }
+    }
+    /// Write the items of a list as `( ... )`.
+    ///
+    /// If the collection does not fit on the line, each item is written on a
+    /// line of its own aligned under the first.
+    fn write_collection<'g>(
+        &mut self,
+        items: &[(&'g BoxTerm, &'g BoxTerm)],
+        level: usize,
+        index: &Index<'g>,
+        written: &mut Written<'g>,
+    ) -> io::Result<()> {
+        if self.try_flat(written, |ser, written| {
+            ser.write_collection(items, level, index, written)
+        })? {
+            return Ok(());
+        }
+        let wrap = self.config.line_width().is_some() && !self.flat;
+
+        self.target.write_all(b"(")?;
+        self.config.write_space(&mut self.target)?;
+        let column = self.target.column();
+        for (i, (_, item)) in items.iter().enumerate() {
+            if i > 0 {
+                if wrap {
+                    self.target.align(column)?;
+                } else {
+                    self.config.write_space(&mut self.target)?;
+                }
+            }
+            self.write_object(item, level, index, written)?;
+        }
+        self.config.write_space(&mut self.target)?;
+        self.target.write_all(b")")
+    }
+    /// Write a blank node as `[ ... ]`.
+    fn write_property_list<'g>(
+        &mut self,
+        bn: &'g BoxTerm,
+        level: usize,
+        index: &Index<'g>,
+        written: &mut Written<'g>,
+    ) -> io::Result<()> {
+        if index.triples_of(bn).is_empty() {
+            return self.target.write_all(b"[]");
+        }
+        if self.try_flat(written, |ser, written| {
+            ser.write_property_list(bn, level, index, written)
+        })? {
+            return Ok(());
+        }
+
+        if self.flat {
+            self.target.write_all(b"[")?;
+            self.config.write_space(&mut self.target)?;
+            self.write_predicates(bn, level + 1, index, written)?;
+            self.config.write_space(&mut self.target)?;
+            return self.target.write_all(b"]");
+        }
+        self.target.write_all(b"[\n")?;
+        self.indent(level + 1)?;
+        self.write_predicates(bn, level + 1, index, written)?;
+        self.target.write_all(b"\n")?;
+        self.indent(level)?;
+        self.target.write_all(b"]")
+    }
+    /// Try to write something on the rest of the current line.
+    ///
+    /// `write` is called with a serializer that writes everything on one
+    /// line. If that fits into the line width, the line is written and `true`
+    /// is returned. Otherwise, nothing is written and blank nodes marked as
+    /// written are reset. Without a line width nothing is tried.
+    fn try_flat<'g, F>(&mut self, written: &mut Written<'g>, write: F) -> io::Result<bool>
+    where
+        F: FnOnce(&mut PrettySerializer<'a, FlatWriter>, &mut Written<'g>) -> io::Result<()>,
+    {
+        let width = match self.config.line_width() {
+            Some(width) if !self.flat => width,
+            _ => return Ok(false),
+        };
+        // leave room for a following `,`, ` ;` or ` .`
+        let left = width.saturating_sub(self.target.column() + 2);
+
+        let mut flat = PrettySerializer {
+            target: ColumnWriter::new(FlatWriter::new(left)),
+            config: self.config,
+            flat: true,
+        };
+        let checkpoint = written.checkpoint();
+        if write(&mut flat, written).is_ok() {
+            self.target
+                .write_all(&flat.target.into_inner().into_inner())?;
+            Ok(true)
+        } else {
+            written.rollback(checkpoint);
+            Ok(false)
+        }
+    }
+    /// Write the indention for the given level.
+    fn indent(&mut self, level: usize) -> io::Result<()> {
+        for _ in 0..level {
+            self.config.write_indent(&mut self.target)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Blank nodes that are already written.
+///
+/// Nodes are logged when they are inserted so a discarded layout can be
+/// rolled back.
+#[derive(Default)]
+struct Written<'g> {
+    nodes: HashSet<&'g BoxTerm>,
+    log: Vec<&'g BoxTerm>,
+}
+
+impl<'g> Written<'g> {
+    /// Checks if `t` is already written.
+    fn contains(&self, t: &BoxTerm) -> bool {
+        self.nodes.contains(t)
+    }
+    /// Mark `t` as written.
+    fn insert(&mut self, t: &'g BoxTerm) {
+        if self.nodes.insert(t) {
+            self.log.push(t);
+        }
+    }
+    /// Returns a checkpoint to roll back to.
+    fn checkpoint(&self) -> usize {
+        self.log.len()
+    }
+    /// Reset all nodes inserted after `checkpoint`.
+    fn rollback(&mut self, checkpoint: usize) {
+        for t in self.log.drain(checkpoint..) {
+            self.nodes.remove(t);
+        }
+    }
+}
+
+/// Index of a sorted graph.
+struct Index<'g> {
+    /// Triples sorted by subject, predicate and object.
+    triples: &'g [[BoxTerm; 3]],
+    /// Subjects in order of appearance.
+    order: Vec<&'g BoxTerm>,
+    /// Range of the triples of each subject.
+    subjects: HashMap<&'g BoxTerm, Range<usize>>,
+    /// Number of times a blank node is used as object.
+    refs: HashMap<&'g BoxTerm, usize>,
+}
+
+impl<'g> Index<'g> {
+    /// Index the triples which must be sorted by subject.
+    fn new(triples: &'g [[BoxTerm; 3]]) -> Self {
+        let mut index = Self {
+            triples,
+            order: vec![],
+            subjects: HashMap::new(),
+            refs: HashMap::new(),
+        };
+
+        for (i, [s, _, o]) in triples.iter().enumerate() {
+            if index.order.last() != Some(&s) {
+                index.order.push(s);
+                index.subjects.insert(s, i..i);
+            }
+            if let Some(range) = index.subjects.get_mut(s) {
+                range.end = i + 1;
+            }
+            if is_bnode(o) {
+                *index.refs.entry(o).or_insert(0) += 1;
+            }
+        }
+
+        index
+    }
+    /// Returns the triples with `s` as subject.
+    fn triples_of(&self, s: &BoxTerm) -> &'g [[BoxTerm; 3]] {
+        match self.subjects.get(s) {
+            Some(range) => &self.triples[range.clone()],
+            None => &[],
+        }
+    }
+    /// Returns how often `t` is used as object.
+    fn refs(&self, t: &BoxTerm) -> usize {
+        self.refs.get(t).copied().unwrap_or(0)
+    }
+    /// Checks if `t` is a blank node that is written where it is referenced.
+    fn is_inlined(&self, t: &BoxTerm) -> bool {
+        is_bnode(t) && self.refs(t) == 1
+    }
+    /// Returns the nodes and items of the list starting at `head`.
+    ///
+    /// Returns `None` if `head` is not the head of a well-formed list, i.e.
+    /// each node is an inlined blank node with exactly one `rdf:first` and
+    /// one `rdf:rest` and the chain ends with `rdf:nil`.
+    fn list_items(
+        &self,
+        head: &'g BoxTerm,
+        written: &Written<'g>,
+    ) -> Option<Vec<(&'g BoxTerm, &'g BoxTerm)>> {
+        let mut items: Vec<(&BoxTerm, &BoxTerm)> = vec![];
+        let mut node = head;
+
+        loop {
+            if !self.is_inlined(node)
+                || written.contains(node)
+                || items.iter().any(|(n, _)| *n == node)
+            {
+                return None;
+            }
+            let (item, next) = match self.triples_of(node) {
+                [[_, p1, o1], [_, p2, o2]]
+                    if is_iri(p1, &rdf::iri::first) && is_iri(p2, &rdf::iri::rest) =>
+                {
+                    (o1, o2)
+                }
+                [[_, p1, o1], [_, p2, o2]]
+                    if is_iri(p1, &rdf::iri::rest) && is_iri(p2, &rdf::iri::first) =>
+                {
+                    (o2, o1)
+                }
+                _ => return None,
+            };
+            items.push((node, item));
+            if is_iri(next, &rdf::iri::nil) {
+                return Some(items);
+            }
+            node = next;
+        }
+    }
+}
+
+/// Order of predicates with the predicates of `order` first.
+fn predicate_key(p: &BoxTerm, order: &[String]) -> (usize, String) {
+    let pos = match p {
+        Term::Iri(iri) => order.iter().position(|o| **o == *iri.value()),
+        _ => None,
+    };
+    (pos.unwrap_or_else(|| order.len()), p.to_string())
+}
+
+/// Checks if `t` is the given IRI.
+fn is_iri(t: &BoxTerm, iri: &Iri<&'static str>) -> bool {
+    match t {
+        Term::Iri(i) => *i.value() == *iri.value(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse::turtle::Parser;
+
+    fn serialize(
+        doc: &str,
+        config: &Config<Turtle>,
+    ) -> std::result::Result<String, Box<dyn std::error::Error>> {
+        let mut ser = PrettySerializer::new(vec![], config)?;
+        ser.serialize(Parser::new(doc))?;
+        Ok(String::from_utf8(ser.finish())?)
+    }
+
+    #[test]
+    fn check_pretty() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let doc = r#"@prefix ex: <http://example.org/> .
+            ex:b ex:p ex:o2, ex:o1 ; a ex:C .
+            ex:a ex:list ( 1 [ ex:q 2 ] ) ;
+                ex:n [ ex:r [ ex:s 3 ] ] ;
+                ex:shared _:x .
+            ex:c ex:shared _:x .
+            _:x ex:v "x" .
+            [ ex:top true ] .
+            ex:a ex:empty [], () .
+            ex:b ex:p ex:o1 .
+        "#;
+        let mut config = Config::<Turtle>::default();
+        config.add_prefix("ex", "http://example.org/".into())?;
+
+        let expected = r#"@prefix ex: <http://example.org/> .
+
+ex:a ex:empty (), [] ;
+    ex:list ( 1 [
+        ex:q 2
+    ] ) ;
+    ex:n [
+        ex:r [
+            ex:s 3
+        ]
+    ] ;
+    ex:shared _:x .
+
+ex:b a ex:C ;
+    ex:p ex:o1, ex:o2 .
+
+ex:c ex:shared _:x .
+
+[
+    ex:top true
+] .
+
+_:x ex:v "x" .
+"#;
+        assert_eq!(serialize(doc, &config)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn check_cycle() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let doc = "_:a <http://ex/p> _:b . _:b <http://ex/p> _:a .";
+        let config = Config::<Turtle>::default();
+
+        let expected = "_:a <http://ex/p> [\n    <http://ex/p> _:a\n] .\n";
+        assert_eq!(serialize(doc, &config)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn check_malformed_list() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let doc = r#"@prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
+            <http://ex/s> <http://ex/p> [ rdf:first 1 ; rdf:rest [ rdf:first 2 ] ] .
+        "#;
+        let mut config = Config::<Turtle>::default();
+        config.add_prefix("rdf", "http://www.w3.org/1999/02/22-rdf-syntax-ns#".into())?;
+
+        let serialized = serialize(doc, &config)?;
+        assert!(!serialized.contains('('));
+        let reparsed = Parser::new(&serialized).collect::<Result<Vec<_>>>()?;
+        assert_eq!(reparsed.len(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn check_canonical() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let doc1 = r#"@prefix ex: <http://example.org/> .
+            _:x ex:p _:y . _:y ex:p _:x .
+            ex:s ex:q _:x, _:y .
+            _:x ex:v 1 . _:y ex:v 2 .
+            ex:s ex:r [ ex:v 3 ] .
+        "#;
+        let doc2 = r#"@prefix ex: <http://example.org/> .
+            ex:s ex:r [ ex:v 3 ] .
+            _:b ex:v 1 . _:a ex:v 2 .
+            ex:s ex:q _:a , _:b .
+            _:a ex:p _:b . _:b ex:p _:a .
+        "#;
+        let mut config = Config::<Turtle>::default();
+        config.add_prefix("ex", "http://example.org/".into())?;
+        config.set_canonical(true);
+
+        let expected = r#"@prefix ex: <http://example.org/> .
+
+ex:s ex:q _:c0, _:c1 ;
+    ex:r [
+        ex:v 3
+    ] .
+
+_:c0 ex:p _:c1 ;
+    ex:v 1 .
+
+_:c1 ex:p _:c0 ;
+    ex:v 2 .
+"#;
+        assert_eq!(serialize(doc1, &config)?, expected);
+        assert_eq!(serialize(doc2, &config)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn check_line_width() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let doc = r#"@prefix ex: <http://example.org/> .
+            ex:s ex:p ex:o1, ex:o2 ;
+                ex:long ex:aaaaaaaaaaaa, ex:bbbbbbbbbbbb, ex:cccccccccccc ;
+                ex:n [ ex:q 1 ] ;
+                ex:list ( 1 2 3 ) ;
+                ex:m [ ex:q ex:aaaaaaaaaaaa ; ex:r ex:bbbbbbbbbbbb ] ;
+                ex:col ( ex:aaaaaaaaaaaa ex:bbbbbbbbbbbb ex:cccccccccccc ) .
+        "#;
+        let mut config = Config::<Turtle>::default();
+        config.add_prefix("ex", "http://example.org/".into())?;
+        config.set_line_width(Some(40));
+
+        let expected = r#"@prefix ex: <http://example.org/> .
+
+ex:s ex:col ( ex:aaaaaaaaaaaa
+              ex:bbbbbbbbbbbb
+              ex:cccccccccccc ) ;
+    ex:list ( 1 2 3 ) ;
+    ex:long ex:aaaaaaaaaaaa,
+            ex:bbbbbbbbbbbb,
+            ex:cccccccccccc ;
+    ex:m [
+        ex:q ex:aaaaaaaaaaaa ;
+        ex:r ex:bbbbbbbbbbbb
+    ] ;
+    ex:n [ ex:q 1 ] ;
+    ex:p ex:o1, ex:o2 .
+"#;
+        let serialized = serialize(doc, &config)?;
+        assert_eq!(serialized, expected);
+        let reparsed = Parser::new(&serialized).collect::<Result<Vec<_>>>()?;
+        assert_eq!(reparsed.len(), 24);
+        Ok(())
+    }
+
+    #[test]
+    fn check_predicate_order() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let doc = r#"@prefix ex: <http://example.org/> .
+            @prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+            ex:s ex:b 2 ; a ex:C ; rdfs:comment "c" ; ex:a 1 ; rdfs:label "l" .
+        "#;
+        let mut config = Config::<Turtle>::with_default_prefixes();
+        config.add_prefix("ex", "http://example.org/".into())?;
+        config
+            .set_predicate_order(vec![
+                "http://www.w3.org/2000/01/rdf-schema#label",
+                "http://www.w3.org/1999/02/22-rdf-syntax-ns#type",
+                "http://www.w3.org/2000/01/rdf-schema#comment",
+            ])
+            .set_type_as_a(false);
+
+        let serialized = serialize(doc, &config)?;
+        let body = serialized.split("\n\n").nth(1).unwrap();
+        assert_eq!(
+            body,
+            "ex:s rdfs:label \"l\" ;\n    \
+             rdf:type ex:C ;\n    \
+             rdfs:comment \"c\" ;\n    \
+             ex:a 1 ;\n    \
+             ex:b 2 .\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn check_list_with_predicate_order() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let doc = r#"@prefix ex: <http://example.org/> .
+            ex:s ex:list ( 1 2 ) .
+        "#;
+        let mut config = Config::<Turtle>::default();
+        config.add_prefix("ex", "http://example.org/".into())?;
+        config.set_predicate_order(vec![
+            "http://www.w3.org/1999/02/22-rdf-syntax-ns#rest",
+            "http://www.w3.org/1999/02/22-rdf-syntax-ns#first",
+        ]);
+
+        let expected = "@prefix ex: <http://example.org/> .\n\nex:s ex:list ( 1 2 ) .\n";
+        assert_eq!(serialize(doc, &config)?, expected);
+        Ok(())
+    }
+}
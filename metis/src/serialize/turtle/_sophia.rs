@@ -0,0 +1,114 @@
+//! Implementation of `sophia`'s serializer API for Turtle.
+
+use super::{PrettySerializer, Turtle};
+use crate::serialize::Config;
+use sophia::serializer::{Stringifier, TripleSerializer};
+use sophia::term::BoxTerm;
+use sophia::triple::{
+    stream::{SinkError, StreamResult, TripleSource},
+    Triple,
+};
+use std::io;
+
+/// `sophia` compatible serializer that writes pretty Turtle.
+///
+/// Each call of `serialize_triples()` writes the preamble and the given
+/// triples as laid out by the [`PrettySerializer`](struct.PrettySerializer.html).
+pub struct TurtleSerializer<W> {
+    config: Config<Turtle>,
+    write: W,
+}
+
+impl<W> TurtleSerializer<W>
+where
+    W: io::Write,
+{
+    /// Create a new serializer with the default configuration.
+    pub fn new(write: W) -> Self {
+        Self::new_with_config(write, Config::default())
+    }
+    /// Create a new serializer with the given configuration.
+    pub fn new_with_config(write: W, config: Config<Turtle>) -> Self {
+        Self { config, write }
+    }
+    /// The configuration of this serializer.
+    pub fn config(&self) -> &Config<Turtle> {
+        &self.config
+    }
+}
+
+impl TurtleSerializer<Vec<u8>> {
+    /// Create a new serializer that writes to memory.
+    pub fn new_stringifier() -> Self {
+        Self::new(vec![])
+    }
+    /// Create a new serializer that writes to memory with the given
+    /// configuration.
+    pub fn new_stringifier_with_config(config: Config<Turtle>) -> Self {
+        Self::new_with_config(vec![], config)
+    }
+}
+
+impl<W> TripleSerializer for TurtleSerializer<W>
+where
+    W: io::Write,
+{
+    type Error = io::Error;
+
+    fn serialize_triples<TS>(
+        &mut self,
+        source: &mut TS,
+    ) -> StreamResult<&mut Self, TS::Error, Self::Error>
+    where
+        TS: TripleSource,
+        Self: Sized,
+    {
+        let mut triples: Vec<[BoxTerm; 3]> = vec![];
+        source.try_for_each_triple(|t| {
+            triples.push([t.s().clone_into(), t.p().clone_into(), t.o().clone_into()]);
+            Ok::<_, io::Error>(())
+        })?;
+
+        let mut ser = PrettySerializer::new(&mut self.write, &self.config).map_err(SinkError)?;
+        ser.write_graph(triples).map_err(SinkError)?;
+
+        Ok(self)
+    }
+}
+
+impl Stringifier for TurtleSerializer<Vec<u8>> {
+    fn as_utf8(&self) -> &[u8] {
+        &self.write[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse::turtle::TurtleParser;
+
+    #[test]
+    fn check_stringifier() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let doc = r#"@prefix ex: <http://example.org/> .
+            ex:s a ex:C ; ex:p ( 1 2 ), [ ex:q "x"@en ] .
+        "#;
+        let mut g: Vec<[BoxTerm; 3]> = vec![];
+        TurtleParser::default().parse_str(doc).in_graph(&mut g)?;
+
+        let mut config = Config::<Turtle>::default();
+        config.add_prefix("ex", "http://example.org/".into())?;
+        let turtle = TurtleSerializer::new_stringifier_with_config(config)
+            .serialize_graph(&g)?
+            .to_string();
+
+        let expected = r#"@prefix ex: <http://example.org/> .
+
+ex:s a ex:C ;
+    ex:p ( 1 2 ), [
+        ex:q "x"@en
+    ] .
+"#;
+        assert_eq!(turtle, expected);
+        Ok(())
+    }
+}
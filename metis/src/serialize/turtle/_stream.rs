@@ -4,7 +4,7 @@ use super::Turtle;
 use crate::error::{Error, Result};
 use crate::serialize::Config;
 use crate::serialize::Serializable;
-use sophia::term::{Term, TermData};
+use sophia::term::Term;
 use sophia::triple::{
     stream::{SinkError, SourceError, StreamError},
     Triple,
@@ -12,26 +12,24 @@ use sophia::triple::{
 use std::io;
 
 /// Serializer for streams of RDF triples.
-pub struct Serializer<'a, T, TD>
+pub struct Serializer<'a, T>
 where
     T: io::Write,
-    TD: TermData,
 {
     target: T,
-    config: &'a Config<Turtle, TD>,
+    config: &'a Config<Turtle>,
     indent_level: u32,
 }
 
-impl<'a, T, TD> Serializer<'a, T, TD>
+impl<'a, T> Serializer<'a, T>
 where
     T: io::Write,
-    TD: TermData,
 {
     /// Create a new serializer for the given target with the given
     /// configuration.
     ///
     /// The preamble for `config` is written immediately.
-    pub fn new(target: T, config: &'a Config<Turtle, TD>) -> io::Result<Self> {
+    pub fn new(target: T, config: &'a Config<Turtle>) -> io::Result<Self> {
         let mut target = target;
         config.write_preamble(&mut target)?;
 
@@ -120,49 +118,52 @@ where
         Ok(())
     }
     /// Finish a block and insert a blank line.
+    ///
+    /// Does nothing if no block was started.
     fn finish_block(&mut self) -> Result<(), Error> {
+        if self.indent_level == 0 {
+            return Ok(());
+        }
         self.target.write_all(b" .\n\n")?;
         self.indent_level = 0;
         Ok(())
     }
 }
 
-// TODO: Find way to test serialization.
-// #[cfg(test)]
-// mod test {
-//     use super::*;
-//     use sophia::graph::inmem::FastGraph;
-//     use sophia::graph::Graph;
-//     use sophia::parser::nt::*;
-//     use sophia::parser::*;
-//     use sophia::triple::stream::TripleSource;
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse::turtle::Parser;
 
-//     #[test]
-//     fn check() -> std::result::Result<(), Box<dyn std::error::Error>> {
-//         let nt = r#"
-// 			_:s1 <http://example.org/p1> _:o1 .
-// 			_:s1 <http://example.org/p2> _:o2 .
-// 			_:s1 <http://example.org/p2> _:o3 .
-// 			_:s2 <http://example.org/p1> _:o1 .
-// 			_:s2 <http://example.org/p2> _:o2 .
-// 			_:s3 <http://example.org/p2> _:o3 .
-//         "#;
+    #[test]
+    fn check_stream() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let doc = r#"
+            <http://example.org/s1> <http://example.org/p1> <http://example.org/o1> .
+            <http://example.org/s1> <http://example.org/p2> <http://example.org/o2> .
+            <http://example.org/s1> <http://example.org/p2> <http://example.org/o3> .
+            <http://example.org/s2> <http://example.org/p1> "lit" .
+        "#;
 
-//         let mut g = FastGraph::new();
-//         let p = NTriplesParser {};
-//         let c = p.parse_str(&nt).in_graph(&mut g)?;
-//         assert_eq!(c, 6);
+        let config = Config::<Turtle>::default();
+        let mut ser = Serializer::new(vec![], &config)?;
+        ser.serialize(Parser::new(doc))?;
 
-//         let mut config = Config::<Turtle, &'static str>::default();
-//         config
-//             .add_default_prefixes()
-//             .set_base("http://example.org/")?;
-//         let mut ser = Serializer::new(vec![], &config)?;
-//         ser.serialize(g.triples())?;
+        let turtle = String::from_utf8(ser.finish()?)?;
+        assert_eq!(
+            turtle,
+            "<http://example.org/s1> <http://example.org/p1> <http://example.org/o1> ;\n    \
+             <http://example.org/p2> <http://example.org/o2>, <http://example.org/o3> .\n\n\
+             <http://example.org/s2> <http://example.org/p1> \"lit\" .\n\n"
+        );
+        Ok(())
+    }
 
-//         let turtle = String::from_utf8(ser.finish()?)?;
-//         println!("serialized:\n{}", turtle);
-//         assert_eq!(&turtle, "_:a1 _:a2");
-//         Ok(())
-//     }
-// }
+    #[test]
+    fn check_empty_stream() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let config = Config::<Turtle>::default();
+        let mut ser = Serializer::new(vec![], &config)?;
+        ser.serialize(Parser::new(""))?;
+        assert!(ser.finish()?.is_empty());
+        Ok(())
+    }
+}